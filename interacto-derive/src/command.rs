@@ -0,0 +1,96 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{DeriveInput, ExprPath, Result, Type};
+
+use crate::memento::memento_fields;
+
+#[derive(Default)]
+struct CommandAttr {
+    execution: Option<ExprPath>,
+    can_execute: Option<ExprPath>,
+    error: Option<Type>,
+}
+
+fn command_attr(input: &DeriveInput) -> Result<CommandAttr> {
+    let mut command = CommandAttr::default();
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("command"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("execution") {
+                command.execution = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("can_execute") {
+                command.can_execute = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("error") {
+                command.error = Some(meta.value()?.parse()?);
+            } else {
+                return Err(meta.error("expected 'execution', 'can_execute' or 'error'"));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(command)
+}
+
+pub(crate) fn expand(input: &DeriveInput) -> Result<TokenStream> {
+    let command = command_attr(input)?;
+    let mementos = memento_fields(input)?;
+    let execution = command.execution.ok_or_else(|| {
+        syn::Error::new_spanned(&input.ident, "missing '#[command(execution = ...)]'")
+    })?;
+    let error = command
+        .error
+        .map(|error| quote!(#error))
+        .unwrap_or_else(|| quote!(::std::convert::Infallible));
+    let captures = mementos.iter().map(|memento| {
+        let ident = &memento.ident;
+        let get = &memento.get;
+        quote! {
+            let state = #get(&*self);
+            ::interacto::memento::Memento::capture(&mut self.#ident, &state);
+        }
+    });
+    let can_execute = command.can_execute.map(|can_execute| {
+        quote! {
+            fn can_execute(&self) -> bool {
+                #can_execute(self)
+            }
+        }
+    });
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::interacto::command::CommandBase for #name #ty_generics #where_clause {
+            fn create_memento(&mut self) {
+                #(#captures)*
+            }
+
+            #can_execute
+        }
+
+        impl #impl_generics ::interacto::command::CustomCmd for #name #ty_generics #where_clause {
+            type Error = #error;
+
+            fn execution(&mut self) -> ::std::result::Result<(), Self::Error> {
+                #execution(self)
+            }
+        }
+    })
+}
@@ -0,0 +1,60 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Derive macros for the commands and undoable objects of Interacto.
+
+mod command;
+mod memento;
+mod undoable;
+
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput};
+
+/**
+ * Implements 'CommandBase' and 'CustomCmd' for a struct.
+ *
+ * Struct attribute: '#[command(execution = path, can_execute = path, error = Type)]'.
+ * - 'execution' (required): a 'fn(&mut Self) -> Result<(), Error>' that executes the command.
+ * - 'can_execute': a 'fn(&Self) -> bool'. By default, the command can always be executed.
+ * - 'error': the error type of the execution. By default, 'std::convert::Infallible'.
+ *
+ * Field attribute: '#[memento(get = path)]' on a 'Memento<S>' field, where 'get' is a 'fn(&Self) -> S'
+ * that gives the state to capture before the first execution.
+ */
+#[proc_macro_derive(Command, attributes(command, memento))]
+pub fn derive_command(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    command::expand(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/**
+ * Implements 'Undoable' for a struct.
+ *
+ * Struct attribute: '#[undoable(name = "...", undo = path, redo = path)]'.
+ * - 'name': the undo name. By default, the name of the struct.
+ * - 'undo' and 'redo': 'fn(&mut Self)' that undo and redo the command.
+ *   By default, the memento fields are restored (in the reverse order) and reapplied.
+ *
+ * Field attribute: '#[memento(get = path, set = path)]' on a 'Memento<S>' field, where 'get' is a
+ * 'fn(&Self) -> S' and 'set' a 'fn(&mut Self, S)' that gives and sets the captured state.
+ */
+#[proc_macro_derive(Undoable, attributes(undoable, memento))]
+pub fn derive_undoable(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    undoable::expand(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
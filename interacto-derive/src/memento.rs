@@ -0,0 +1,75 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use syn::{Data, DeriveInput, ExprPath, Fields, Ident, Result};
+
+/**
+ * A 'Memento' field of a struct, with the functions that give and set its state.
+ */
+pub(crate) struct MementoField {
+    pub(crate) ident: Ident,
+    pub(crate) get: Option<ExprPath>,
+    pub(crate) set: Option<ExprPath>,
+}
+
+/**
+ * @returns The fields marked with '#[memento(...)]', in their order of declaration.
+ */
+pub(crate) fn memento_fields(input: &DeriveInput) -> Result<Vec<MementoField>> {
+    let fields = match &input.data {
+        Data::Struct(data) => &data.fields,
+        _ => return Err(syn::Error::new_spanned(input, "only structs are supported")),
+    };
+    let mut mementos = Vec::new();
+    for field in fields {
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("memento"))
+        {
+            let ident = match (&field.ident, fields) {
+                (Some(ident), Fields::Named(_)) => ident.clone(),
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        attr,
+                        "memento fields must be named",
+                    ))
+                }
+            };
+            let mut memento = MementoField {
+                ident,
+                get: None,
+                set: None,
+            };
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("get") {
+                    memento.get = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("set") {
+                    memento.set = Some(meta.value()?.parse()?);
+                } else {
+                    return Err(meta.error("expected 'get' or 'set'"));
+                }
+                Ok(())
+            })?;
+            if memento.get.is_none() {
+                return Err(syn::Error::new_spanned(
+                    attr,
+                    "missing 'get' in the memento attribute",
+                ));
+            }
+            mementos.push(memento);
+        }
+    }
+    Ok(mementos)
+}
@@ -0,0 +1,116 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{DeriveInput, ExprPath, LitStr, Result};
+
+use crate::memento::{memento_fields, MementoField};
+
+#[derive(Default)]
+struct UndoableAttr {
+    name: Option<LitStr>,
+    undo: Option<ExprPath>,
+    redo: Option<ExprPath>,
+}
+
+fn undoable_attr(input: &DeriveInput) -> Result<UndoableAttr> {
+    let mut undoable = UndoableAttr::default();
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("undoable"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("name") {
+                undoable.name = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("undo") {
+                undoable.undo = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("redo") {
+                undoable.redo = Some(meta.value()?.parse()?);
+            } else {
+                return Err(meta.error("expected 'name', 'undo' or 'redo'"));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(undoable)
+}
+
+/**
+ * @returns The statements that restore ('restore') or reapply ('reapply') the state of a memento field.
+ */
+fn memento_statement(memento: &MementoField, operation: &str) -> Result<TokenStream> {
+    let ident = &memento.ident;
+    let get = &memento.get;
+    let set = memento
+        .set
+        .as_ref()
+        .ok_or_else(|| syn::Error::new_spanned(ident, "missing 'set' in the memento attribute"))?;
+    let operation = syn::Ident::new(operation, ident.span());
+    Ok(quote! {
+        let mut state = #get(&*self);
+        ::interacto::memento::Memento::#operation(&mut self.#ident, &mut state);
+        #set(self, state);
+    })
+}
+
+pub(crate) fn expand(input: &DeriveInput) -> Result<TokenStream> {
+    let undoable = undoable_attr(input)?;
+    let mementos = memento_fields(input)?;
+    let name = &input.ident;
+
+    if mementos.is_empty() && (undoable.undo.is_none() || undoable.redo.is_none()) {
+        return Err(syn::Error::new_spanned(
+            name,
+            "undoable commands need memento fields or '#[undoable(undo = ..., redo = ...)]'",
+        ));
+    }
+
+    let undo = match &undoable.undo {
+        Some(undo) => quote!(#undo(self);),
+        None => mementos
+            .iter()
+            .rev()
+            .map(|memento| memento_statement(memento, "restore"))
+            .collect::<Result<TokenStream>>()?,
+    };
+    let redo = match &undoable.redo {
+        Some(redo) => quote!(#redo(self);),
+        None => mementos
+            .iter()
+            .map(|memento| memento_statement(memento, "reapply"))
+            .collect::<Result<TokenStream>>()?,
+    };
+    let undo_name = undoable
+        .name
+        .unwrap_or_else(|| LitStr::new(&name.to_string(), name.span()));
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::interacto::undoble::Undoable for #name #ty_generics #where_clause {
+            fn undo(&mut self) {
+                #undo
+            }
+
+            fn redo(&mut self) {
+                #redo
+            }
+
+            fn get_undo_name(&self) -> ::std::string::String {
+                ::std::string::String::from(#undo_name)
+            }
+        }
+    })
+}
@@ -0,0 +1,228 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::{cell::RefCell, rc::Rc};
+
+use interacto::{
+    command::{CmdOutcome, Command, CustomCmd},
+    historyhandle::HistoryHandle,
+    linearhistory::LinearHistoryImpl,
+    memento::Memento,
+    undoble::Undoable,
+    Command, Undoable,
+};
+
+#[derive(Clone, PartialEq, Debug, Default)]
+struct Shape {
+    x: i32,
+    color: String,
+}
+
+#[derive(Command, Undoable)]
+#[command(execution = Self::apply, can_execute = Self::is_valid)]
+#[undoable(name = "Move and paint")]
+struct MoveAndPaint {
+    shape: Rc<RefCell<Shape>>,
+    dx: i32,
+    color: &'static str,
+    #[memento(get = Self::get_x, set = Self::set_x)]
+    x: Memento<i32>,
+    #[memento(get = Self::get_color, set = Self::set_color)]
+    old_color: Memento<String>,
+}
+
+impl MoveAndPaint {
+    fn new(shape: &Rc<RefCell<Shape>>, dx: i32, color: &'static str) -> Self {
+        Self {
+            shape: shape.clone(),
+            dx,
+            color,
+            x: Memento::new(),
+            old_color: Memento::new(),
+        }
+    }
+
+    fn apply(&mut self) -> Result<(), std::convert::Infallible> {
+        let mut shape = self.shape.borrow_mut();
+        shape.x += self.dx;
+        shape.color = self.color.to_string();
+        Ok(())
+    }
+
+    fn is_valid(&self) -> bool {
+        self.dx != 0
+    }
+
+    fn get_x(&self) -> i32 {
+        self.shape.borrow().x
+    }
+
+    fn set_x(&mut self, x: i32) {
+        self.shape.borrow_mut().x = x;
+    }
+
+    fn get_color(&self) -> String {
+        self.shape.borrow().color.clone()
+    }
+
+    fn set_color(&mut self, color: String) {
+        self.shape.borrow_mut().color = color;
+    }
+}
+
+#[derive(Command, Undoable)]
+#[command(execution = Self::push, error = String)]
+#[undoable(undo = Self::pop, redo = Self::push_again)]
+struct Push {
+    stack: Rc<RefCell<Vec<i32>>>,
+    value: i32,
+}
+
+impl Push {
+    fn push(&mut self) -> Result<(), String> {
+        if self.value < 0 {
+            return Err("negative value".to_string());
+        }
+        self.stack.borrow_mut().push(self.value);
+        Ok(())
+    }
+
+    fn pop(&mut self) {
+        self.stack.borrow_mut().pop();
+    }
+
+    fn push_again(&mut self) {
+        self.stack.borrow_mut().push(self.value);
+    }
+}
+
+fn shape() -> Rc<RefCell<Shape>> {
+    Rc::new(RefCell::new(Shape {
+        x: 1,
+        color: "red".to_string(),
+    }))
+}
+
+#[test]
+fn command_execution() {
+    let shape = shape();
+    let mut cmd = Command::new(MoveAndPaint::new(&shape, 10, "blue"));
+    assert!(cmd.execute().is_executed());
+    assert_eq!(shape.borrow().x, 11);
+    assert_eq!(shape.borrow().color, "blue");
+}
+
+#[test]
+fn command_can_execute() {
+    let shape = shape();
+    let mut cmd = Command::new(MoveAndPaint::new(&shape, 0, "blue"));
+    assert!(!cmd.can_execute());
+    assert!(matches!(cmd.execute(), CmdOutcome::NotExecutable));
+}
+
+#[test]
+fn command_captures_mementos() {
+    let shape = shape();
+    let mut cmd = Command::new(MoveAndPaint::new(&shape, 10, "blue"));
+    cmd.execute();
+    cmd.execute();
+    assert_eq!(cmd.child.x.get_state(), Some(&1));
+    assert_eq!(
+        cmd.child.old_color.get_state().map(String::as_str),
+        Some("red")
+    );
+}
+
+#[test]
+fn undoable_from_mementos() {
+    let shape = shape();
+    let history = HistoryHandle::new(LinearHistoryImpl::new());
+    let mut cmd = Command::new(MoveAndPaint::new(&shape, 10, "blue"));
+    cmd.execute();
+    cmd.set_history(history.clone());
+    cmd.done();
+    assert!(cmd.child.is_in_history());
+    history.undo();
+    assert_eq!(
+        *shape.borrow(),
+        Shape {
+            x: 1,
+            color: "red".to_string()
+        }
+    );
+    history.redo();
+    assert_eq!(
+        *shape.borrow(),
+        Shape {
+            x: 11,
+            color: "blue".to_string()
+        }
+    );
+}
+
+#[test]
+fn undo_name_attribute() {
+    let cmd = MoveAndPaint::new(&shape(), 10, "blue");
+    assert_eq!(cmd.get_undo_name(), "Move and paint");
+}
+
+#[test]
+fn default_undo_name() {
+    let cmd = Push {
+        stack: Rc::default(),
+        value: 1,
+    };
+    assert_eq!(cmd.get_undo_name(), "Push");
+}
+
+#[test]
+fn undo_redo_functions() {
+    let stack = Rc::new(RefCell::new(Vec::new()));
+    let mut cmd = Push {
+        stack: stack.clone(),
+        value: 1,
+    };
+    cmd.execution().unwrap();
+    cmd.undo();
+    assert!(stack.borrow().is_empty());
+    cmd.redo();
+    assert_eq!(*stack.borrow(), vec![1]);
+}
+
+#[test]
+fn error_type() {
+    let mut cmd = Command::new(Push {
+        stack: Rc::default(),
+        value: -1,
+    });
+    assert!(matches!(cmd.execute(), CmdOutcome::Failed(error) if error == "negative value"));
+}
+
+#[test]
+fn undoable_with_functions_added_to_history() {
+    let stack = Rc::new(RefCell::new(Vec::new()));
+    let history = HistoryHandle::new(LinearHistoryImpl::new());
+    let mut cmd = Command::new(Push {
+        stack: stack.clone(),
+        value: 1,
+    });
+    cmd.execute();
+    cmd.set_history(history.clone());
+    cmd.done();
+    assert!(cmd.child.is_in_history());
+    history.undo();
+    assert!(stack.borrow().is_empty());
+    history.redo();
+    assert_eq!(*stack.borrow(), vec![1]);
+}
@@ -11,17 +11,21 @@
  * You should have received a copy of the GNU General Public License
  * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
  */
-use crate::command::CustomCmd;
+use std::convert::Infallible;
+
+use crate::command::{CommandBase, CustomCmd};
 
 /**
  * An anonymous command that takes an anonymous function as a parameter corresponding to the command to execute.
  * The goal of this command is to avoid the creation of a command class for a small command.
  */
+#[allow(clippy::unused_unit)]
 pub struct AnonCmd<'a> {
     exec: Box<dyn 'a + FnMut() -> ()>
 }
 
 impl<'a> AnonCmd<'a> {
+    #[allow(clippy::unused_unit)]
     pub fn new(fct: impl 'a + FnMut() -> ()) -> Self {
         Self {
             exec: Box::new(fct)
@@ -29,12 +33,17 @@ impl<'a> AnonCmd<'a> {
     }
 }
 
-impl<'a> CustomCmd for AnonCmd<'a> {
-    fn execution(&mut self) {
-        (self.exec)()
-    }
-
+impl<'a> CommandBase for AnonCmd<'a> {
     fn can_execute(&self) -> bool {
         true
     }
 }
+
+impl<'a> CustomCmd for AnonCmd<'a> {
+    type Error = Infallible;
+
+    fn execution(&mut self) -> Result<(), Self::Error> {
+        (self.exec)();
+        Ok(())
+    }
+}
@@ -0,0 +1,90 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::{
+    future::{poll_fn, Future},
+    panic::{self, AssertUnwindSafe},
+    pin::pin,
+    task::Poll,
+};
+
+use crate::command::{panic_message, CmdOutcome, CmdStatus, Command, CommandBase};
+
+/**
+ * A command whose execution is asynchronous, for example to perform I/O without blocking the UI thread.
+ * The returned futures are not required to be 'Send', so that they can be driven by any executor,
+ * including a single-threaded one.
+ * Its memento and execution condition are defined by 'CommandBase', as for synchronous commands.
+ */
+#[allow(async_fn_in_trait)]
+pub trait AsyncCustomCmd: CommandBase {
+    /**
+     * The type of the errors the execution of the command may produce.
+     */
+    type Error;
+
+    /**
+     * This method contains the statements to execute the command.
+     * This method is automatically called by 'execute_async' and must not be called explicitly.
+     * @returns An error if the execution failed.
+     */
+    async fn execution(&mut self) -> Result<(), Self::Error>;
+}
+
+impl<T: AsyncCustomCmd> Command<T> {
+    /**
+     * Executes the command if its status and its 'can_execute' predicate allow it.
+     * The command is marked as executed once its execution completes, even if it fails.
+     * When the command catches panics (see 'Command::set_catch_panics'), a panic raised while
+     * polling the execution completes it with 'CmdOutcome::Panicked'.
+     */
+    pub async fn execute_async(&mut self) -> CmdOutcome<T::Error> {
+        let status = self.get_status();
+        if (status == CmdStatus::Created || status == CmdStatus::Executed) && self.child.can_execute() {
+            if status == CmdStatus::Created {
+                self.child.create_memento();
+            }
+
+            let result = if self.is_catching_panics() {
+                let caught = {
+                    let mut execution = pin!(self.child.execution());
+                    poll_fn(|cx| {
+                        match panic::catch_unwind(AssertUnwindSafe(|| execution.as_mut().poll(cx))) {
+                            Ok(Poll::Pending) => Poll::Pending,
+                            Ok(Poll::Ready(result)) => Poll::Ready(Ok(result)),
+                            Err(payload) => Poll::Ready(Err(payload)),
+                        }
+                    })
+                    .await
+                };
+                match caught {
+                    Ok(result) => result,
+                    Err(payload) => {
+                        self.set_status(CmdStatus::Executed);
+                        return CmdOutcome::Panicked(panic_message(payload));
+                    }
+                }
+            } else {
+                self.child.execution().await
+            };
+            self.set_status(CmdStatus::Executed);
+            match result {
+                Ok(()) => CmdOutcome::Executed,
+                Err(error) => CmdOutcome::Failed(error),
+            }
+        } else {
+            CmdOutcome::NotExecutable
+        }
+    }
+}
@@ -0,0 +1,48 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use crate::{
+    event::UIEvent,
+    fsm::Fsm,
+    interaction::{
+        fsm_interaction, is_pointer_away, is_pointer_down, is_pointer_move, is_pointer_up, point_data, PointData,
+    },
+};
+
+/**
+ * A click: the press and the release of a pointer button, without moving farther than 'CLICK_TOLERANCE'.
+ * Its data is the one of the release.
+ */
+pub struct Click {
+    fsm: Fsm<UIEvent, PointData>,
+}
+
+impl Click {
+    pub fn new() -> Self {
+        let mut fsm: Fsm<UIEvent, PointData> = Fsm::new();
+        let init = fsm.get_init_state();
+        let pressed = fsm.add_std_state("pressed");
+        let clicked = fsm.add_terminal_state("clicked");
+        let cancelled = fsm.add_cancelling_state("cancelled");
+        fsm.add_transition(init, pressed, is_pointer_down)
+            .with_action(|event, data| *data = point_data(event).unwrap_or_default());
+        fsm.add_transition(pressed, clicked, |event| matches!(event, UIEvent::PointerUp(_)))
+            .with_guard(|event, data| is_pointer_up(event, data.button))
+            .with_action(|event, data| *data = point_data(event).unwrap_or_default());
+        fsm.add_transition(pressed, cancelled, is_pointer_move).with_guard(is_pointer_away);
+        Self { fsm }
+    }
+}
+
+fsm_interaction!(Click, PointData, Default);
@@ -0,0 +1,99 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::time::Duration;
+
+use crate::{
+    event::UIEvent,
+    fsm::Fsm,
+    interaction::{
+        fsm_interaction, is_pointer_away, is_pointer_down, is_pointer_move, is_pointer_up, point_data, PointData,
+    },
+};
+
+/**
+ * The data of a 'Clicks' interaction.
+ */
+#[derive(PartialEq, Clone, Debug, Default)]
+pub struct ClicksData {
+    /**
+     * The data of the releases of the clicks, in their order.
+     */
+    pub points: Vec<PointData>,
+    /**
+     * The press of the first click. Its button is the one of all the clicks, and moving the pointer
+     * farther than 'CLICK_TOLERANCE' from it cancels the interaction.
+     */
+    pub press: PointData,
+}
+
+/**
+ * A given number of clicks of the same button, each one starting less than 'delay' after the previous one.
+ */
+pub struct Clicks {
+    fsm: Fsm<UIEvent, ClicksData>,
+    count: usize,
+}
+
+impl Clicks {
+    /**
+     * The default max delay between two clicks.
+     */
+    pub const DEFAULT_DELAY: Duration = Duration::from_millis(1000);
+
+    /**
+     * @param count - The number of clicks. Must be greater than 0.
+     */
+    pub fn new(count: usize) -> Self {
+        Self::with_delay(count, Self::DEFAULT_DELAY)
+    }
+
+    /**
+     * @param count - The number of clicks. Must be greater than 0.
+     * @param delay - The max delay between the release of a click and the press of the next one.
+     */
+    pub fn with_delay(count: usize, delay: Duration) -> Self {
+        assert!(count > 0, "the number of clicks must be greater than 0");
+        let mut fsm: Fsm<UIEvent, ClicksData> = Fsm::new();
+        let init = fsm.get_init_state();
+        let pressed = fsm.add_std_state("pressed");
+        let clicked = fsm.add_std_state("clicked");
+        let ended = fsm.add_terminal_state("ended");
+        let cancelled = fsm.add_cancelling_state("cancelled");
+        let push_point = |event: &UIEvent, data: &mut ClicksData| data.points.extend(point_data(event));
+        fsm.add_transition(init, pressed, is_pointer_down)
+            .with_action(|event, data| data.press = point_data(event).unwrap_or_default());
+        fsm.add_transition(clicked, pressed, is_pointer_down)
+            .with_guard(|event, data| point_data(event).is_some_and(|point| point.button == data.press.button));
+        fsm.add_transition(pressed, ended, |event| matches!(event, UIEvent::PointerUp(_)))
+            .with_guard(move |event, data| is_pointer_up(event, data.press.button) && data.points.len() + 1 == count)
+            .with_action(push_point);
+        fsm.add_transition(pressed, clicked, |event| matches!(event, UIEvent::PointerUp(_)))
+            .with_guard(|event, data| is_pointer_up(event, data.press.button))
+            .with_action(push_point);
+        for state in [pressed, clicked] {
+            fsm.add_transition(state, cancelled, is_pointer_move)
+                .with_guard(|event, data| is_pointer_away(event, &data.press));
+        }
+        fsm.add_transition(clicked, cancelled, is_pointer_down);
+        fsm.add_timeout_transition(clicked, cancelled, delay);
+        Self { fsm, count }
+    }
+
+    pub fn get_count(&self) -> usize {
+        self.count
+    }
+}
+
+fsm_interaction!(Clicks, ClicksData);
@@ -0,0 +1,84 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::{cell::Cell, rc::Rc, time::Duration, time::Instant};
+
+/**
+ * Gives the current time, as the time elapsed since an arbitrary origin.
+ * FSMs use it for their timeout transitions and histories for their merge policy:
+ * inject a 'VirtualClock' to control the time in tests.
+ */
+pub trait Clock {
+    fn now(&self) -> Duration;
+}
+
+/**
+ * The clock of the system: the time elapsed since the creation of the clock.
+ */
+#[derive(Clone, Copy, Debug)]
+pub struct SystemClock {
+    origin: Instant,
+}
+
+impl SystemClock {
+    pub fn new() -> Self {
+        Self {
+            origin: Instant::now(),
+        }
+    }
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.origin.elapsed()
+    }
+}
+
+/**
+ * A clock whose time only changes when it is advanced explicitly.
+ * Clones share the same time, so that a test can keep a clone to advance the clock of an FSM.
+ */
+#[derive(Clone, Debug, Default)]
+pub struct VirtualClock {
+    time: Rc<Cell<Duration>>,
+}
+
+impl VirtualClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /**
+     * Moves the time forward.
+     */
+    pub fn advance(&self, duration: Duration) {
+        self.time.set(self.time.get() + duration);
+    }
+
+    pub fn set(&self, time: Duration) {
+        self.time.set(time);
+    }
+}
+
+impl Clock for VirtualClock {
+    fn now(&self) -> Duration {
+        self.time.get()
+    }
+}
@@ -12,7 +12,9 @@
  * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-use crate::undoble::Undoable;
+use std::{any::Any, error::Error, fmt, ops::{Deref, DerefMut}, panic::{self, AssertUnwindSafe}};
+
+use crate::{historyhandle::HistoryHandle, undoble::Undoable};
 
 /**
  * Defines the different states of the command.
@@ -31,95 +33,193 @@ pub enum CmdStatus {
     Flushed,
 }
 
+impl CmdStatus {
+    /**
+     * Checks the lifecycle of commands: Created -> Executed -> Done,
+     * where the command can be executed several times and marked as done without being executed.
+     * A command can be cancelled or flushed at any time.
+     * @returns True if a command can go from this status to the given one.
+     */
+    pub fn can_transition_to(self, to: CmdStatus) -> bool {
+        match to {
+            CmdStatus::Cancelled | CmdStatus::Flushed => true,
+            CmdStatus::Created => false,
+            CmdStatus::Executed => self == CmdStatus::Created || self == CmdStatus::Executed,
+            CmdStatus::Done => self == CmdStatus::Created || self == CmdStatus::Executed,
+        }
+    }
+}
+
+/**
+ * The error produced by an illegal change of the status of a command.
+ */
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct IllegalTransition {
+    pub from: CmdStatus,
+    pub to: CmdStatus,
+}
+
+impl fmt::Display for IllegalTransition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "a command cannot go from {:?} to {:?}", self.from, self.to)
+    }
+}
+
+impl Error for IllegalTransition {}
+
+/**
+ * The outcome of the execution of a command.
+ */
+#[derive(PartialEq, Clone, Debug)]
+pub enum CmdOutcome<E> {
+    /** The command cannot be executed: its status or its 'can_execute' predicate prevents it. */
+    NotExecutable,
+    /** The command has been executed. */
+    Executed,
+    /** The execution of the command failed with the given error. */
+    Failed(E),
+    /**
+     * The execution of the command panicked, with the given panic message.
+     * Only produced by commands that catch panics (see 'Command::set_catch_panics').
+     */
+    Panicked(String),
+}
+
+impl<E> CmdOutcome<E> {
+    /**
+     * @returns True if the command has been executed without error.
+     */
+    pub fn is_executed(&self) -> bool {
+        matches!(self, CmdOutcome::Executed)
+    }
+}
+
 /**
  * A command is produced and executed in reaction of a user interaction.
  * It follows the command design pattern.
  * It contains statements to execute to perform the command.
  * The interface Undoable can be used to add undo/redo features to a command.
  */
-pub struct Command<T: CustomCmd> {
+pub struct Command<T> {
     status: CmdStatus,
-    pub child: T
+    /**
+     * Whether panics raised by the execution are caught.
+     */
+    catch_panics: bool,
+    /**
+     * Gives the child to the undo history the command is attached to, once the command is done.
+     */
+    to_history: Option<Box<dyn FnOnce(T)>>,
+    pub child: CmdChild<T>
 }
 
-impl<T: CustomCmd> Command<T> {
+impl<T> Command<T> {
     pub fn new(child_cmd: T) -> Self {
         Self {
             status: CmdStatus::Created,
-            child: child_cmd
+            catch_panics: false,
+            to_history: None,
+            child: CmdChild(Some(child_cmd))
         }
     }
 }
 
-impl<T: CustomCmd> Command<T> {
+/**
+ * The child of a command, that is the custom command it runs. It dereferences to the custom command.
+ * Once a command attached to an undo history is done, its child belongs to the history:
+ * dereferencing it then panics (see 'is_in_history').
+ */
+pub struct CmdChild<T>(Option<T>);
+
+impl<T> CmdChild<T> {
+    /**
+     * @returns The custom command, or none if it has been given to an undo history.
+     */
+    pub fn get(&self) -> Option<&T> {
+        self.0.as_ref()
+    }
+
+    /**
+     * @returns The custom command, or none if it has been given to an undo history.
+     */
+    pub fn get_mut(&mut self) -> Option<&mut T> {
+        self.0.as_mut()
+    }
+
+    /**
+     * @returns True if the custom command has been given to an undo history.
+     */
+    pub fn is_in_history(&self) -> bool {
+        self.0.is_none()
+    }
+}
+
+impl<T> Deref for CmdChild<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.0.as_ref().expect("the command has been given to its undo history")
+    }
+}
+
+impl<T> DerefMut for CmdChild<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.0.as_mut().expect("the command has been given to its undo history")
+    }
+}
+
+impl<T> Command<T> {
     pub fn get_status(&self) -> CmdStatus {
         self.status
     }
 
-    pub fn set_status(&mut self, new_status: CmdStatus) {
+    /**
+     * Changes the status without checking the lifecycle of the command.
+     */
+    pub(crate) fn set_status(&mut self, new_status: CmdStatus) {
         self.status = new_status
     }
 
-    pub fn execute(&mut self) -> bool {
-        let ok: bool;
-        let status = self.get_status();
-        if (status == CmdStatus::Created || status == CmdStatus::Executed) && self.child.can_execute() {
-            if status == CmdStatus::Created {
-                self.child.create_memento();
-            }
-            ok = true;
-
-            //     try {
-            self.execution();
-            //         if (result instanceof Promise) {
-            //             return result
-            //                 .then(() => {
-            //                     this.status = "executed";
-            //                     return true;
-            //                 })
-            //                 .catch(() => {
-            //                     this.status = "executed";
-            //                     return false;
-            //                 });
-            //         }
-            //     } catch (error: unknown) {
-            //         this.status = "executed";
-            //         throw error;
-            //     }
-            self.set_status(CmdStatus::Executed);
-        } else {
-            ok = false;
-        }
-        ok
-    }
-
     /**
-     * Actions may need to create a memento before their first execution.
-     * This is the goal of the operation that should be overridden.
-     * This operator is called a single time before the first execution of the command.
+     * Changes the status of the command if the lifecycle of commands allows it.
+     * The 'Executed' status cannot be set this way: only 'execute' and 'execute_async' can reach it.
+     * @returns An error if the command cannot go from its current status to the given one.
      */
-    pub fn create_memento(&mut self) {
-        self.child.create_memento()
+    pub fn transition_to(&mut self, new_status: CmdStatus) -> Result<(), IllegalTransition> {
+        if new_status != CmdStatus::Executed && self.status.can_transition_to(new_status) {
+            self.set_status(new_status);
+            Ok(())
+        } else {
+            Err(IllegalTransition {
+                from: self.status,
+                to: new_status,
+            })
+        }
     }
 
-    pub fn can_execute(&self) -> bool {
-        self.child.can_execute()
+    pub fn is_catching_panics(&self) -> bool {
+        self.catch_panics
     }
 
     /**
-     * This method contains the statements to execute the command.
-     * This method is automatically called by 'execute' and must not be called explicitly.
+     * When enabled, a panic raised by the execution of the command is caught:
+     * 'execute' then returns 'CmdOutcome::Panicked' and the command is marked as executed,
+     * so that the caller can keep running.
+     * Disabled by default.
      */
-    pub fn execution(&mut self) {
-        self.child.execution()
+    pub fn set_catch_panics(&mut self, catch_panics: bool) {
+        self.catch_panics = catch_panics
     }
 
     /**
-     * Marks the command as "done" and sends it to the command registry.
+     * Marks the command as "done".
+     * If the command is attached to an undo history (see 'set_history'), its child is added to the history.
      */
     pub fn done(&mut self) {
-        if self.get_status() == CmdStatus::Created || self.get_status() == CmdStatus::Executed {
-            self.set_status(CmdStatus::Done);
+        if self.transition_to(CmdStatus::Done).is_ok() {
+            if let Some(to_history) = self.to_history.take() {
+                to_history(self.child.0.take().expect("a command is done only once"));
+            }
         }
     }
 
@@ -144,7 +244,101 @@ impl<T: CustomCmd> Command<T> {
     }
 }
 
-pub trait CustomCmd: Sized {
+impl<T: CustomCmd> Command<T> {
+    /**
+     * Executes the command if its status and its 'can_execute' predicate allow it.
+     * The command is marked as executed even if its execution fails.
+     */
+    pub fn execute(&mut self) -> CmdOutcome<T::Error> {
+        let status = self.get_status();
+        if (status == CmdStatus::Created || status == CmdStatus::Executed) && self.child.can_execute() {
+            if status == CmdStatus::Created {
+                self.child.create_memento();
+            }
+
+            let result = if self.catch_panics {
+                match panic::catch_unwind(AssertUnwindSafe(|| self.execution())) {
+                    Ok(result) => result,
+                    Err(payload) => {
+                        self.set_status(CmdStatus::Executed);
+                        return CmdOutcome::Panicked(panic_message(payload));
+                    }
+                }
+            } else {
+                self.execution()
+            };
+            self.set_status(CmdStatus::Executed);
+            match result {
+                Ok(()) => CmdOutcome::Executed,
+                Err(error) => CmdOutcome::Failed(error),
+            }
+        } else {
+            CmdOutcome::NotExecutable
+        }
+    }
+
+    /**
+     * This method contains the statements to execute the command.
+     * This method is automatically called by 'execute' and must not be called explicitly.
+     */
+    pub fn execution(&mut self) -> Result<(), T::Error> {
+        self.child.execution()
+    }
+}
+
+impl<T: CommandBase> Command<T> {
+    /**
+     * Actions may need to create a memento before their first execution.
+     * This is the goal of the operation that should be overridden.
+     * This operator is called a single time before the first execution of the command.
+     */
+    pub fn create_memento(&mut self) {
+        if let Some(child) = self.child.get_mut() {
+            child.create_memento()
+        }
+    }
+
+    pub fn can_execute(&self) -> bool {
+        self.child.get().is_some_and(|child| child.can_execute())
+    }
+}
+
+impl<T: Undoable + 'static> Command<T> {
+    /**
+     * Attaches the undoable command to an undo history: once marked as done, the command is added
+     * to the history. Replaces the history previously attached.
+     * @param history - The history that receives the command.
+     */
+    pub fn set_history(&mut self, history: impl Into<HistoryHandle>) {
+        let history = history.into();
+        self.to_history = Some(Box::new(move |child| history.add(Box::new(child))));
+    }
+
+    /**
+     * @returns True if the command is attached to an undo history that has not received it yet.
+     */
+    pub fn has_history(&self) -> bool {
+        self.to_history.is_some()
+    }
+}
+
+/**
+ * Extracts the message of a panic payload.
+ */
+pub(crate) fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(msg) => *msg,
+        Err(payload) => payload
+            .downcast_ref::<&str>()
+            .map(|msg| msg.to_string())
+            .unwrap_or_default(),
+    }
+}
+
+/**
+ * The operations shared by the synchronous ('CustomCmd') and asynchronous ('AsyncCustomCmd') commands.
+ */
+pub trait CommandBase: Sized {
     /**
      * Actions may need to create a memento before their first execution.
      * This is the goal of the operation that should be overridden.
@@ -157,15 +351,30 @@ pub trait CustomCmd: Sized {
         true
     }
 
+    #[allow(clippy::wrong_self_convention)]
+    fn as_command(self) -> Command<Self> {
+        Command::new(self)
+    }
+}
+
+pub trait CustomCmd: CommandBase {
+    /**
+     * The type of the errors the execution of the command may produce.
+     * Commands that cannot fail can use 'std::convert::Infallible'.
+     */
+    type Error;
+
     /**
      * This method contains the statements to execute the command.
      * This method is automatically called by 'execute' and must not be called explicitly.
+     * @returns An error if the execution failed.
      */
-    fn execution(&mut self);
-
-    fn as_command(self) -> Command<Self> {
-        Command::new(self)
-    }
+    fn execution(&mut self) -> Result<(), Self::Error>;
 }
 
-pub trait UndoableCommand<'a>: CustomCmd + Undoable {}
+/**
+ * A command that can be undone and redone.
+ */
+pub trait UndoableCommand: CustomCmd + Undoable {}
+
+impl<T: CustomCmd + Undoable> UndoableCommand for T {}
@@ -0,0 +1,87 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use crate::{
+    event::UIEvent,
+    fsm::Fsm,
+    interaction::{
+        fsm_interaction, is_escape, is_pointer_down, is_pointer_drag, is_pointer_move, is_pointer_up, point_data,
+        SrcTgtPointsData,
+    },
+};
+
+/**
+ * Builds the FSM of a drag-and-drop: press, moves with the pressed button held down, release.
+ * The interaction starts on the first move: releasing the button without moving cancels it silently.
+ * @param cancellable - True if pressing Escape during the drag cancels the interaction.
+ */
+fn dnd_fsm(cancellable: bool) -> Fsm<UIEvent, SrcTgtPointsData> {
+    let mut fsm: Fsm<UIEvent, SrcTgtPointsData> = Fsm::new();
+    let init = fsm.get_init_state();
+    let pressed = fsm.add_std_state("pressed");
+    let dragged = fsm.add_std_state("dragged");
+    let released = fsm.add_terminal_state("released");
+    let cancelled = fsm.add_cancelling_state("cancelled");
+    fsm.set_starting_state(dragged);
+    fsm.add_transition(init, pressed, is_pointer_down).with_action(|event, data| {
+        let point = point_data(event).unwrap_or_default();
+        data.src = point;
+        data.tgt = point;
+    });
+    for src in [pressed, dragged] {
+        fsm.add_transition(src, dragged, is_pointer_move)
+            .with_guard(|event, data| is_pointer_drag(event, data.src.button))
+            .with_action(|event, data| data.tgt = point_data(event).unwrap_or_default());
+        if cancellable {
+            fsm.add_transition(src, cancelled, is_escape);
+        }
+    }
+    fsm.add_transition(pressed, cancelled, |event| matches!(event, UIEvent::PointerUp(_)))
+        .with_guard(|event, data| is_pointer_up(event, data.src.button));
+    fsm.add_transition(dragged, released, |event| matches!(event, UIEvent::PointerUp(_)))
+        .with_guard(|event, data| is_pointer_up(event, data.src.button))
+        .with_action(|event, data| data.tgt = point_data(event).unwrap_or_default());
+    fsm
+}
+
+/**
+ * A drag-and-drop: the press of a pointer button, moves, and the release of the button.
+ * Its data gives the press point and the last point.
+ */
+pub struct DnD {
+    fsm: Fsm<UIEvent, SrcTgtPointsData>,
+}
+
+impl DnD {
+    pub fn new() -> Self {
+        Self { fsm: dnd_fsm(false) }
+    }
+}
+
+fsm_interaction!(DnD, SrcTgtPointsData, Default);
+
+/**
+ * A drag-and-drop that pressing the Escape key cancels.
+ */
+pub struct CancellableDnD {
+    fsm: Fsm<UIEvent, SrcTgtPointsData>,
+}
+
+impl CancellableDnD {
+    pub fn new() -> Self {
+        Self { fsm: dnd_fsm(true) }
+    }
+}
+
+fsm_interaction!(CancellableDnD, SrcTgtPointsData, Default);
@@ -0,0 +1,76 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::time::Duration;
+
+use crate::{
+    event::UIEvent,
+    fsm::Fsm,
+    interaction::{
+        fsm_interaction, is_pointer_away, is_pointer_down, is_pointer_move, is_pointer_up, point_data, PointData,
+    },
+};
+
+/**
+ * Two clicks of the same button, the second one starting less than 'delay' after the first one.
+ * Its data is the one of the first press.
+ */
+pub struct DoubleClick {
+    fsm: Fsm<UIEvent, PointData>,
+    delay: Duration,
+}
+
+impl DoubleClick {
+    /**
+     * The default max delay between the two clicks.
+     */
+    pub const DEFAULT_DELAY: Duration = Duration::from_millis(300);
+
+    pub fn new() -> Self {
+        Self::with_delay(Self::DEFAULT_DELAY)
+    }
+
+    /**
+     * @param delay - The max delay between the release of the first click and the press of the second one.
+     */
+    pub fn with_delay(delay: Duration) -> Self {
+        let mut fsm: Fsm<UIEvent, PointData> = Fsm::new();
+        let init = fsm.get_init_state();
+        let pressed = fsm.add_std_state("pressed");
+        let clicked = fsm.add_std_state("clicked");
+        let pressed_again = fsm.add_std_state("pressedAgain");
+        let double_clicked = fsm.add_terminal_state("doubleClicked");
+        let cancelled = fsm.add_cancelling_state("cancelled");
+        fsm.add_transition(init, pressed, is_pointer_down)
+            .with_action(|event, data| *data = point_data(event).unwrap_or_default());
+        fsm.add_transition(pressed, clicked, |event| matches!(event, UIEvent::PointerUp(_)))
+            .with_guard(|event, data| is_pointer_up(event, data.button));
+        fsm.add_transition(clicked, pressed_again, is_pointer_down)
+            .with_guard(|event, data| point_data(event).is_some_and(|point| point.button == data.button));
+        fsm.add_transition(pressed_again, double_clicked, |event| matches!(event, UIEvent::PointerUp(_)))
+            .with_guard(|event, data| is_pointer_up(event, data.button));
+        for state in [pressed, clicked, pressed_again] {
+            fsm.add_transition(state, cancelled, is_pointer_move).with_guard(is_pointer_away);
+        }
+        fsm.add_transition(clicked, cancelled, is_pointer_down);
+        fsm.add_timeout_transition(clicked, cancelled, delay);
+        Self { fsm, delay }
+    }
+
+    pub fn get_delay(&self) -> Duration {
+        self.delay
+    }
+}
+
+fsm_interaction!(DoubleClick, PointData, Default);
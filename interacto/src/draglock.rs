@@ -0,0 +1,107 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::time::Duration;
+
+use crate::{
+    doubleclick::DoubleClick,
+    event::UIEvent,
+    fsm::Fsm,
+    interaction::{
+        fsm_interaction, is_escape, is_pointer_away, is_pointer_down, is_pointer_hover, is_pointer_move, is_pointer_up,
+        point_data, SrcTgtPointsData,
+    },
+};
+
+/**
+ * A drag lock: a double-click locks the drag, the pointer moves without pressing any button,
+ * and a second double-click releases the drag. Pressing Escape cancels the interaction.
+ * The interaction starts once the drag is locked.
+ * Its data gives the point of the first double-click and the last position of the pointer.
+ */
+pub struct DragLock {
+    fsm: Fsm<UIEvent, SrcTgtPointsData>,
+    delay: Duration,
+}
+
+impl DragLock {
+    pub fn new() -> Self {
+        Self::with_delay(DoubleClick::DEFAULT_DELAY)
+    }
+
+    /**
+     * @param delay - The max delay between the two clicks of a double-click.
+     */
+    pub fn with_delay(delay: Duration) -> Self {
+        let mut fsm: Fsm<UIEvent, SrcTgtPointsData> = Fsm::new();
+        let init = fsm.get_init_state();
+        let pressed = fsm.add_std_state("pressed");
+        let clicked = fsm.add_std_state("clicked");
+        let pressed_again = fsm.add_std_state("pressedAgain");
+        let locked = fsm.add_std_state("locked");
+        let end_pressed = fsm.add_std_state("endPressed");
+        let end_clicked = fsm.add_std_state("endClicked");
+        let end_pressed_again = fsm.add_std_state("endPressedAgain");
+        let released = fsm.add_terminal_state("released");
+        let cancelled = fsm.add_cancelling_state("cancelled");
+        fsm.set_starting_state(locked);
+        let is_up = |event: &UIEvent| matches!(event, UIEvent::PointerUp(_));
+        let same_button = |event: &UIEvent, data: &SrcTgtPointsData| {
+            point_data(event).is_some_and(|point| point.button == data.src.button)
+        };
+
+        // Locking double-click.
+        fsm.add_transition(init, pressed, is_pointer_down).with_action(|event, data| {
+            let point = point_data(event).unwrap_or_default();
+            data.src = point;
+            data.tgt = point;
+        });
+        fsm.add_transition(pressed, clicked, is_up)
+            .with_guard(|event, data| is_pointer_up(event, data.src.button));
+        fsm.add_transition(clicked, pressed_again, is_pointer_down)
+            .with_guard(same_button);
+        fsm.add_transition(pressed_again, locked, is_up)
+            .with_guard(|event, data| is_pointer_up(event, data.src.button));
+        for state in [pressed, clicked, pressed_again] {
+            fsm.add_transition(state, cancelled, is_pointer_move)
+                .with_guard(|event, data| is_pointer_away(event, &data.src));
+        }
+        fsm.add_timeout_transition(clicked, cancelled, delay);
+
+        // Locked moves and releasing double-click.
+        for state in [locked, end_pressed, end_clicked, end_pressed_again] {
+            fsm.add_transition(state, locked, is_pointer_hover)
+                .with_action(|event, data| data.tgt = point_data(event).unwrap_or_default());
+        }
+        fsm.add_transition(locked, end_pressed, is_pointer_down).with_guard(same_button);
+        fsm.add_transition(end_pressed, end_clicked, is_up)
+            .with_guard(|event, data| is_pointer_up(event, data.src.button));
+        fsm.add_transition(end_clicked, end_pressed_again, is_pointer_down)
+            .with_guard(same_button);
+        fsm.add_transition(end_pressed_again, released, is_up)
+            .with_guard(|event, data| is_pointer_up(event, data.src.button));
+        fsm.add_timeout_transition(end_clicked, locked, delay);
+
+        for state in [pressed, clicked, pressed_again, locked, end_pressed, end_clicked, end_pressed_again] {
+            fsm.add_transition(state, cancelled, is_escape);
+        }
+        Self { fsm, delay }
+    }
+
+    pub fn get_delay(&self) -> Duration {
+        self.delay
+    }
+}
+
+fsm_interaction!(DragLock, SrcTgtPointsData, Default);
@@ -0,0 +1,344 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+/**
+ * The identifier of a widget, given by the adapter of the GUI library.
+ */
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub struct WidgetId(pub u64);
+
+/**
+ * The modifier keys pressed during an event.
+ */
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug, Default)]
+pub struct Modifiers {
+    pub shift: bool,
+    pub ctrl: bool,
+    pub alt: bool,
+    pub meta: bool,
+}
+
+impl Modifiers {
+    pub const NONE: Modifiers = Modifiers {
+        shift: false,
+        ctrl: false,
+        alt: false,
+        meta: false,
+    };
+
+    /**
+     * @returns True if no modifier key is pressed.
+     */
+    pub fn is_empty(&self) -> bool {
+        *self == Self::NONE
+    }
+}
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug, Default)]
+pub enum MouseButton {
+    /** Usually the left button. */
+    #[default]
+    Primary,
+    /** Usually the right button. */
+    Secondary,
+    /** Usually the wheel button. */
+    Middle,
+    Other(u16),
+}
+
+/**
+ * A set of mouse buttons, for example the buttons held down during a pointer event.
+ * The 'MouseButton::Other' buttons are tracked up to 'Other(28)'.
+ */
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug, Default)]
+pub struct ButtonSet(u32);
+
+impl ButtonSet {
+    pub const EMPTY: ButtonSet = ButtonSet(0);
+
+    fn mask(button: MouseButton) -> u32 {
+        match button {
+            MouseButton::Primary => 1,
+            MouseButton::Secondary => 1 << 1,
+            MouseButton::Middle => 1 << 2,
+            MouseButton::Other(index) => 1u32.checked_shl(3 + u32::from(index)).unwrap_or(0),
+        }
+    }
+
+    pub fn with(mut self, button: MouseButton) -> Self {
+        self.insert(button);
+        self
+    }
+
+    pub fn insert(&mut self, button: MouseButton) {
+        self.0 |= Self::mask(button);
+    }
+
+    pub fn remove(&mut self, button: MouseButton) {
+        self.0 &= !Self::mask(button);
+    }
+
+    pub fn contains(&self, button: MouseButton) -> bool {
+        self.0 & Self::mask(button) != 0
+    }
+
+    /**
+     * @returns True if no button is in the set.
+     */
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl From<MouseButton> for ButtonSet {
+    fn from(button: MouseButton) -> Self {
+        Self::EMPTY.with(button)
+    }
+}
+
+/**
+ * An event of a mouse, a pen or any other pointing device.
+ */
+#[derive(PartialEq, Clone, Copy, Debug, Default)]
+pub struct PointerEvent {
+    pub x: f64,
+    pub y: f64,
+    /**
+     * The button that changed state. For moves, 'Primary' by convention: see 'buttons'.
+     */
+    pub button: MouseButton,
+    /**
+     * The buttons held down once the event occurred: a press includes its button, a release
+     * does not. It distinguishes a drag (some buttons held) from a hover move (none held).
+     */
+    pub buttons: ButtonSet,
+    pub modifiers: Modifiers,
+    /**
+     * The widget under the pointer.
+     */
+    pub target: Option<WidgetId>,
+}
+
+impl PointerEvent {
+    pub fn new(x: f64, y: f64, button: MouseButton) -> Self {
+        Self {
+            x,
+            y,
+            button,
+            buttons: ButtonSet::EMPTY,
+            modifiers: Modifiers::NONE,
+            target: None,
+        }
+    }
+
+    pub fn with_buttons(mut self, buttons: impl Into<ButtonSet>) -> Self {
+        self.buttons = buttons.into();
+        self
+    }
+
+    pub fn with_modifiers(mut self, modifiers: Modifiers) -> Self {
+        self.modifiers = modifiers;
+        self
+    }
+
+    pub fn with_target(mut self, target: WidgetId) -> Self {
+        self.target = Some(target);
+        self
+    }
+}
+
+/**
+ * A scroll of a mouse wheel or of a touchpad.
+ */
+#[derive(PartialEq, Clone, Copy, Debug, Default)]
+pub struct WheelEvent {
+    pub x: f64,
+    pub y: f64,
+    pub delta_x: f64,
+    pub delta_y: f64,
+    pub delta_z: f64,
+    pub modifiers: Modifiers,
+    pub target: Option<WidgetId>,
+}
+
+/**
+ * A press or a release of a key.
+ */
+#[derive(PartialEq, Eq, Clone, Debug, Default)]
+pub struct KeyEvent {
+    /**
+     * The code of the physical key, for example "KeyA", "Enter" or "Escape".
+     */
+    pub code: String,
+    /**
+     * The text the key produces, if any.
+     */
+    pub text: Option<String>,
+    pub modifiers: Modifiers,
+    /**
+     * True if the key is held down and the event is repeated.
+     */
+    pub repeat: bool,
+    pub target: Option<WidgetId>,
+}
+
+impl KeyEvent {
+    pub const ESCAPE: &'static str = "Escape";
+
+    pub fn new(code: &str) -> Self {
+        Self {
+            code: code.to_string(),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_text(mut self, text: &str) -> Self {
+        self.text = Some(text.to_string());
+        self
+    }
+
+    pub fn with_modifiers(mut self, modifiers: Modifiers) -> Self {
+        self.modifiers = modifiers;
+        self
+    }
+
+    pub fn with_target(mut self, target: WidgetId) -> Self {
+        self.target = Some(target);
+        self
+    }
+
+    pub fn is_escape(&self) -> bool {
+        self.code == Self::ESCAPE
+    }
+}
+
+/**
+ * An event of a touch point, identified by 'id' from its start to its end.
+ */
+#[derive(PartialEq, Clone, Copy, Debug, Default)]
+pub struct TouchEvent {
+    pub id: u64,
+    pub x: f64,
+    pub y: f64,
+    pub modifiers: Modifiers,
+    pub target: Option<WidgetId>,
+}
+
+impl TouchEvent {
+    pub fn new(id: u64, x: f64, y: f64) -> Self {
+        Self {
+            id,
+            x,
+            y,
+            modifiers: Modifiers::NONE,
+            target: None,
+        }
+    }
+
+    pub fn with_target(mut self, target: WidgetId) -> Self {
+        self.target = Some(target);
+        self
+    }
+}
+
+/**
+ * The new value of a widget (check box, slider, text field, list, etc.).
+ */
+#[derive(PartialEq, Clone, Debug)]
+pub enum WidgetValue {
+    Bool(bool),
+    Number(f64),
+    Text(String),
+    /** The index of the selected item, if any. */
+    Selection(Option<usize>),
+}
+
+/**
+ * An input event, independent of any GUI library: the adapters of GUI libraries convert
+ * their events into this type to feed FSMs and interactions.
+ */
+#[derive(PartialEq, Clone, Debug)]
+pub enum UIEvent {
+    PointerDown(PointerEvent),
+    PointerMove(PointerEvent),
+    PointerUp(PointerEvent),
+    Wheel(WheelEvent),
+    KeyDown(KeyEvent),
+    KeyUp(KeyEvent),
+    TouchStart(TouchEvent),
+    TouchMove(TouchEvent),
+    TouchEnd(TouchEvent),
+    FocusIn(WidgetId),
+    FocusOut(WidgetId),
+    ValueChanged { target: WidgetId, value: WidgetValue },
+}
+
+impl UIEvent {
+    /**
+     * @returns The widget the event targets, if any.
+     */
+    pub fn get_target(&self) -> Option<WidgetId> {
+        match self {
+            UIEvent::PointerDown(event) | UIEvent::PointerMove(event) | UIEvent::PointerUp(event) => event.target,
+            UIEvent::Wheel(event) => event.target,
+            UIEvent::KeyDown(event) | UIEvent::KeyUp(event) => event.target,
+            UIEvent::TouchStart(event) | UIEvent::TouchMove(event) | UIEvent::TouchEnd(event) => event.target,
+            UIEvent::FocusIn(target) | UIEvent::FocusOut(target) => Some(*target),
+            UIEvent::ValueChanged { target, .. } => Some(*target),
+        }
+    }
+
+    /**
+     * @returns The modifier keys pressed during the event (none for focus and value events).
+     */
+    pub fn get_modifiers(&self) -> Modifiers {
+        match self {
+            UIEvent::PointerDown(event) | UIEvent::PointerMove(event) | UIEvent::PointerUp(event) => event.modifiers,
+            UIEvent::Wheel(event) => event.modifiers,
+            UIEvent::KeyDown(event) | UIEvent::KeyUp(event) => event.modifiers,
+            UIEvent::TouchStart(event) | UIEvent::TouchMove(event) | UIEvent::TouchEnd(event) => event.modifiers,
+            UIEvent::FocusIn(_) | UIEvent::FocusOut(_) | UIEvent::ValueChanged { .. } => Modifiers::NONE,
+        }
+    }
+
+    /**
+     * @returns The pointer event, if the event is one.
+     */
+    pub fn as_pointer(&self) -> Option<&PointerEvent> {
+        match self {
+            UIEvent::PointerDown(event) | UIEvent::PointerMove(event) | UIEvent::PointerUp(event) => Some(event),
+            _ => None,
+        }
+    }
+
+    /**
+     * @returns The key event, if the event is one.
+     */
+    pub fn as_key(&self) -> Option<&KeyEvent> {
+        match self {
+            UIEvent::KeyDown(event) | UIEvent::KeyUp(event) => Some(event),
+            _ => None,
+        }
+    }
+
+    /**
+     * @returns The touch event, if the event is one.
+     */
+    pub fn as_touch(&self) -> Option<&TouchEvent> {
+        match self {
+            UIEvent::TouchStart(event) | UIEvent::TouchMove(event) | UIEvent::TouchEnd(event) => Some(event),
+            _ => None,
+        }
+    }
+}
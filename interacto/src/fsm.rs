@@ -0,0 +1,665 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::time::Duration;
+
+use crate::{
+    clock::{Clock, SystemClock},
+    command::{CmdStatus, Command, CustomCmd, UndoableCommand},
+    historyhandle::HistoryHandle,
+    subject::{Subject, Subscription},
+};
+
+/**
+ * The identifier of a state in its FSM.
+ */
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub struct StateId(usize);
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum StateKind {
+    /** The initial state of an FSM. */
+    Init,
+    /** A state in which the FSM waits for the next events. */
+    Standard,
+    /** A state that stops the FSM: the interaction ends normally. */
+    Terminal,
+    /** A state that cancels the FSM: the interaction is aborted. */
+    Cancelling,
+}
+
+pub struct State {
+    name: String,
+    kind: StateKind,
+}
+
+impl State {
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_kind(&self) -> StateKind {
+        self.kind
+    }
+}
+
+/**
+ * The steps of the life of an FSM, published to its observers.
+ */
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum FsmStep {
+    /** The FSM reached its starting state: by default, it left its initial state. */
+    Started,
+    /** The FSM entered a standard state after it started (after 'Started' the first time). */
+    Updated,
+    /** The FSM reached a terminal state after it started. */
+    Stopped,
+    /** The FSM reached a cancelling state, or has been cancelled, after it started. */
+    Cancelled,
+    /** The FSM went back to its initial state and its data has been reset. */
+    Reinit,
+}
+
+/**
+ * An observer of an FSM, notified with the data of the FSM.
+ */
+pub type FsmObserver<C> = dyn FnMut(FsmStep, &C);
+
+type Guard<E, C> = Box<dyn Fn(&E, &C) -> bool>;
+
+type Action<E, C> = Box<dyn FnMut(&E, &mut C)>;
+
+type EpsilonGuard<C> = Box<dyn Fn(&C) -> bool>;
+
+type EpsilonAction<C> = Box<dyn FnMut(&mut C)>;
+
+/**
+ * A transition triggered by an event.
+ */
+pub struct Transition<E, C> {
+    src: StateId,
+    tgt: StateId,
+    accept: Box<dyn Fn(&E) -> bool>,
+    guard: Option<Guard<E, C>>,
+    action: Option<Action<E, C>>,
+}
+
+impl<E, C> Transition<E, C> {
+    pub fn get_src(&self) -> StateId {
+        self.src
+    }
+
+    pub fn get_tgt(&self) -> StateId {
+        self.tgt
+    }
+
+    /**
+     * Adds a predicate that must be true for the transition to be fired.
+     */
+    pub fn with_guard(&mut self, guard: impl Fn(&E, &C) -> bool + 'static) -> &mut Self {
+        self.guard = Some(Box::new(guard));
+        self
+    }
+
+    /**
+     * Sets the action executed when the transition is fired, before entering the target state.
+     * Actions typically update the data of the FSM.
+     */
+    pub fn with_action(&mut self, action: impl FnMut(&E, &mut C) + 'static) -> &mut Self {
+        self.action = Some(Box::new(action));
+        self
+    }
+
+    fn accepts(&self, event: &E, data: &C) -> bool {
+        (self.accept)(event) && self.guard.as_ref().is_none_or(|guard| guard(event, data))
+    }
+}
+
+/**
+ * A transition fired without event, as soon as its source state is entered.
+ */
+pub struct EpsilonTransition<C> {
+    src: StateId,
+    tgt: StateId,
+    guard: Option<EpsilonGuard<C>>,
+    action: Option<EpsilonAction<C>>,
+}
+
+impl<C> EpsilonTransition<C> {
+    pub fn get_src(&self) -> StateId {
+        self.src
+    }
+
+    pub fn get_tgt(&self) -> StateId {
+        self.tgt
+    }
+
+    /**
+     * Adds a predicate that must be true for the transition to be fired.
+     */
+    pub fn with_guard(&mut self, guard: impl Fn(&C) -> bool + 'static) -> &mut Self {
+        self.guard = Some(Box::new(guard));
+        self
+    }
+
+    /**
+     * Sets the action executed when the transition is fired, before entering the target state.
+     */
+    pub fn with_action(&mut self, action: impl FnMut(&mut C) + 'static) -> &mut Self {
+        self.action = Some(Box::new(action));
+        self
+    }
+
+    fn accepts(&self, data: &C) -> bool {
+        self.guard.as_ref().is_none_or(|guard| guard(data))
+    }
+}
+
+/**
+ * A transition fired when its source state has been the current state for a given duration.
+ */
+pub struct TimeoutTransition<C> {
+    src: StateId,
+    tgt: StateId,
+    timeout: Duration,
+    guard: Option<EpsilonGuard<C>>,
+    action: Option<EpsilonAction<C>>,
+}
+
+impl<C> TimeoutTransition<C> {
+    pub fn get_src(&self) -> StateId {
+        self.src
+    }
+
+    pub fn get_tgt(&self) -> StateId {
+        self.tgt
+    }
+
+    pub fn get_timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /**
+     * Adds a predicate, checked when the timeout expires, that must be true for the transition to be fired.
+     */
+    pub fn with_guard(&mut self, guard: impl Fn(&C) -> bool + 'static) -> &mut Self {
+        self.guard = Some(Box::new(guard));
+        self
+    }
+
+    /**
+     * Sets the action executed when the transition is fired, before entering the target state.
+     */
+    pub fn with_action(&mut self, action: impl FnMut(&mut C) + 'static) -> &mut Self {
+        self.action = Some(Box::new(action));
+        self
+    }
+}
+
+/**
+ * A finite-state machine that describes a user interaction.
+ * 'E' is the type of the processed events and 'C' the type of the data the FSM builds while running
+ * (reset to its default value when the FSM is reinitialised).
+ * When the FSM reaches a terminal state it stops, when it reaches a cancelling state it is cancelled:
+ * in both cases it then goes back to its initial state.
+ * The FSM starts when it leaves its initial state, or when it reaches its starting state if it has one:
+ * before, it notifies its observers of nothing but its reinitialisations.
+ * Timeout transitions are checked against the clock of the FSM, on each processed event and on each call
+ * to 'check_timeout': the host application calls it when the deadline (see 'get_deadline') is reached.
+ */
+pub struct Fsm<E: 'static, C: Default + 'static> {
+    states: Vec<State>,
+    transitions: Vec<Transition<E, C>>,
+    epsilons: Vec<EpsilonTransition<C>>,
+    timeouts: Vec<TimeoutTransition<C>>,
+    clock: Box<dyn Clock>,
+
+    /**
+     * When the timeout transition of the current state expires, on the clock of the FSM.
+     */
+    deadline: Option<Duration>,
+    current: StateId,
+    /**
+     * The state that starts the FSM, if the FSM does not start as soon as it leaves its initial state.
+     */
+    starting: Option<StateId>,
+    started: bool,
+    data: C,
+    publisher: Subject<FsmObserver<C>>,
+}
+
+impl<E: 'static, C: Default + 'static> Fsm<E, C> {
+    pub fn new() -> Self {
+        Self {
+            states: vec![State {
+                name: "init".to_string(),
+                kind: StateKind::Init,
+            }],
+            transitions: Vec::new(),
+            epsilons: Vec::new(),
+            timeouts: Vec::new(),
+            clock: Box::new(SystemClock::new()),
+            deadline: None,
+            current: StateId(0),
+            starting: None,
+            started: false,
+            data: C::default(),
+            publisher: Subject::new(),
+        }
+    }
+
+    fn add_state(&mut self, name: &str, kind: StateKind) -> StateId {
+        self.states.push(State {
+            name: name.to_string(),
+            kind,
+        });
+        StateId(self.states.len() - 1)
+    }
+
+    pub fn add_std_state(&mut self, name: &str) -> StateId {
+        self.add_state(name, StateKind::Standard)
+    }
+
+    pub fn add_terminal_state(&mut self, name: &str) -> StateId {
+        self.add_state(name, StateKind::Terminal)
+    }
+
+    pub fn add_cancelling_state(&mut self, name: &str) -> StateId {
+        self.add_state(name, StateKind::Cancelling)
+    }
+
+    /**
+     * Adds a transition fired by the events the 'accept' predicate accepts.
+     * Transitions are tried in their order of addition: the first one that accepts an event is fired.
+     * @returns The transition, to add a guard or an action.
+     */
+    pub fn add_transition(
+        &mut self,
+        src: StateId,
+        tgt: StateId,
+        accept: impl Fn(&E) -> bool + 'static,
+    ) -> &mut Transition<E, C> {
+        self.transitions.push(Transition {
+            src,
+            tgt,
+            accept: Box::new(accept),
+            guard: None,
+            action: None,
+        });
+        self.transitions.last_mut().unwrap()
+    }
+
+    /**
+     * Adds a transition fired as soon as its source state is entered (if its guard allows it).
+     * @returns The transition, to add a guard or an action.
+     */
+    pub fn add_epsilon_transition(&mut self, src: StateId, tgt: StateId) -> &mut EpsilonTransition<C> {
+        self.epsilons.push(EpsilonTransition {
+            src,
+            tgt,
+            guard: None,
+            action: None,
+        });
+        self.epsilons.last_mut().unwrap()
+    }
+
+    /**
+     * Adds a transition fired when its source state has been the current state for the given duration.
+     * A state has a single timeout: only its first timeout transition is used.
+     * The source must be a standard state: the FSM leaves the other states as soon as it enters them.
+     * @returns The transition, to add a guard or an action.
+     */
+    pub fn add_timeout_transition(&mut self, src: StateId, tgt: StateId, timeout: Duration) -> &mut TimeoutTransition<C> {
+        assert!(
+            self.get_state(src).kind == StateKind::Standard,
+            "the source of a timeout transition must be a standard state"
+        );
+        self.timeouts.push(TimeoutTransition {
+            src,
+            tgt,
+            timeout,
+            guard: None,
+            action: None,
+        });
+        self.timeouts.last_mut().unwrap()
+    }
+
+    /**
+     * Sets the state that starts the FSM, for example the first move of a drag-and-drop rather than
+     * its press. By default, the FSM starts as soon as it leaves its initial state.
+     */
+    pub fn set_starting_state(&mut self, state: StateId) {
+        self.starting = Some(state);
+    }
+
+    /**
+     * @returns The state that starts the FSM, if it does not start as soon as it leaves its initial state.
+     */
+    pub fn get_starting_state(&self) -> Option<StateId> {
+        self.starting
+    }
+
+    /**
+     * Replaces the clock used by the timeout transitions (the system clock by default).
+     */
+    pub fn set_clock(&mut self, clock: Box<dyn Clock>) {
+        self.clock = clock;
+    }
+
+    pub fn get_clock(&self) -> &dyn Clock {
+        self.clock.as_ref()
+    }
+
+    /**
+     * @returns When the timeout of the current state expires, on the clock of the FSM, if it has one.
+     */
+    pub fn get_deadline(&self) -> Option<Duration> {
+        self.deadline
+    }
+
+    /**
+     * Fires the timeout transition of the current state if its deadline is reached.
+     * A timeout whose guard fails stays pending: it is checked again on the next call.
+     * @returns True if a transition has been fired.
+     */
+    pub fn check_timeout(&mut self) -> bool {
+        if self.deadline.is_none_or(|deadline| self.clock.now() < deadline) {
+            return false;
+        }
+        let current = self.current;
+        let data = &self.data;
+        let Some(timeout) = self.timeouts.iter_mut().find(|timeout| timeout.src == current) else {
+            self.deadline = None;
+            return false;
+        };
+        if !timeout.guard.as_ref().is_none_or(|guard| guard(data)) {
+            return false;
+        }
+        if let Some(action) = &mut timeout.action {
+            action(&mut self.data);
+        }
+        let tgt = timeout.tgt;
+        self.enter(tgt);
+        true
+    }
+
+    pub fn get_init_state(&self) -> StateId {
+        StateId(0)
+    }
+
+    pub fn get_current_state(&self) -> StateId {
+        self.current
+    }
+
+    pub fn get_state(&self, id: StateId) -> &State {
+        &self.states[id.0]
+    }
+
+    /**
+     * @returns True if the FSM reached its starting state and is not finished.
+     */
+    pub fn is_started(&self) -> bool {
+        self.started
+    }
+
+    /**
+     * @returns The data built by the FSM since its last reinitialisation.
+     */
+    pub fn get_data(&self) -> &C {
+        &self.data
+    }
+
+    /**
+     * Observes the steps of the FSM (start, update, stop, cancellation, reinitialisation).
+     * @returns The subscription of the observer.
+     */
+    pub fn subscribe(&self, observer: Box<FsmObserver<C>>) -> Subscription {
+        self.publisher.subscribe(observer)
+    }
+
+    /**
+     * Processes an event: fires the first transition of the current state that accepts it.
+     * An expired timeout is fired first.
+     * @returns True if a transition has been fired for the event.
+     */
+    pub fn process(&mut self, event: &E) -> bool {
+        self.check_timeout();
+        let current = self.current;
+        let data = &self.data;
+        let Some(transition) = self
+            .transitions
+            .iter_mut()
+            .find(|transition| transition.src == current && transition.accepts(event, data))
+        else {
+            return false;
+        };
+        if let Some(action) = &mut transition.action {
+            action(event, &mut self.data);
+        }
+        let tgt = transition.tgt;
+        self.enter(tgt);
+        true
+    }
+
+    /**
+     * Cancels the FSM, whatever its current state, and reinitialises it.
+     */
+    pub fn cancel(&mut self) {
+        if self.started {
+            self.publish(FsmStep::Cancelled);
+        }
+        self.reinit();
+    }
+
+    /**
+     * Puts the FSM back in its initial state and resets its data, without stopping or cancelling it.
+     */
+    pub fn reinit(&mut self) {
+        self.current = self.get_init_state();
+        self.deadline = None;
+        self.started = false;
+        self.data = C::default();
+        self.publish(FsmStep::Reinit);
+    }
+
+    fn enter(&mut self, state: StateId) {
+        self.current = state;
+        self.deadline = self
+            .timeouts
+            .iter()
+            .find(|timeout| timeout.src == state)
+            .map(|timeout| self.clock.now() + timeout.timeout);
+        match self.get_state(state).kind {
+            StateKind::Init => self.reinit(),
+            StateKind::Standard => {
+                self.start(state);
+                if self.started {
+                    self.publish(FsmStep::Updated);
+                }
+                self.fire_epsilon();
+            }
+            StateKind::Terminal => {
+                self.start(state);
+                if self.started {
+                    self.publish(FsmStep::Stopped);
+                }
+                self.reinit();
+            }
+            StateKind::Cancelling => self.cancel(),
+        }
+    }
+
+    /**
+     * Starts the FSM if the given state is its starting state.
+     */
+    fn start(&mut self, state: StateId) {
+        if !self.started && self.starting.is_none_or(|starting| starting == state) {
+            self.started = true;
+            self.publish(FsmStep::Started);
+        }
+    }
+
+    fn fire_epsilon(&mut self) {
+        let current = self.current;
+        let data = &self.data;
+        if let Some(epsilon) = self
+            .epsilons
+            .iter_mut()
+            .find(|epsilon| epsilon.src == current && epsilon.accepts(data))
+        {
+            if let Some(action) = &mut epsilon.action {
+                action(&mut self.data);
+            }
+            let tgt = epsilon.tgt;
+            self.enter(tgt);
+        }
+    }
+
+    fn publish(&self, step: FsmStep) {
+        self.publisher.notify(|observer| observer(step, &self.data));
+    }
+}
+
+impl<E: 'static, C: Default + 'static> Default for Fsm<E, C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/**
+ * Creates an FSM observer that, each time the FSM stops, creates a command from the data of the FSM
+ * and executes it. Executed commands are marked as done.
+ * @param factory - Creates the command from the data of the FSM.
+ */
+pub fn execute_on_stop<C, T: CustomCmd>(mut factory: impl FnMut(&C) -> T + 'static) -> Box<FsmObserver<C>> {
+    Box::new(move |step, data| {
+        if step == FsmStep::Stopped {
+            let mut cmd = Command::new(factory(data));
+            if cmd.execute().is_executed() {
+                cmd.done();
+            }
+        }
+    })
+}
+
+/**
+ * Creates an FSM observer that, each time the FSM stops, creates an undoable command from the data
+ * of the FSM and executes it. Executed commands are marked as done and added to the history.
+ * @param factory - Creates the command from the data of the FSM.
+ * @param history - The history that receives the commands.
+ */
+pub fn execute_on_stop_in<C, T: UndoableCommand + 'static>(
+    mut factory: impl FnMut(&C) -> T + 'static,
+    history: impl Into<HistoryHandle>,
+) -> Box<FsmObserver<C>> {
+    let history = history.into();
+    Box::new(move |step, data| {
+        if step == FsmStep::Stopped {
+            let mut cmd = Command::new(factory(data));
+            cmd.set_history(history.clone());
+            if cmd.execute().is_executed() {
+                cmd.done();
+            }
+        }
+    })
+}
+
+/**
+ * Creates an FSM observer that executes a command while the FSM runs, for example to preview a
+ * resize during a drag-and-drop. The command is created when the FSM starts (for a drag-and-drop,
+ * on its first move: see 'Fsm::set_starting_state'), then updated from the data of the FSM and
+ * executed again on each update and when the FSM stops ('Command::execute' allows executing an
+ * executed command again). When the FSM stops, the command is marked as done.
+ * When the FSM is cancelled, or if the last execution fails, the command is cancelled: its effects
+ * are not undone (see 'execute_continuously_in' for undoable commands).
+ * @param factory - Creates the command from the data of the FSM when it starts.
+ * @param update - Updates the command from the data of the FSM before each execution.
+ */
+pub fn execute_continuously<C, T: CustomCmd + 'static>(
+    mut factory: impl FnMut(&C) -> T + 'static,
+    update: impl FnMut(&mut T, &C) + 'static,
+) -> Box<FsmObserver<C>> {
+    continuously(move |data| Command::new(factory(data)), update, |cmd| cmd.cancel())
+}
+
+/**
+ * Creates an FSM observer that executes an undoable command while the FSM runs, for example to move
+ * a shape during a drag-and-drop. The command is executed as with 'execute_continuously'. When the
+ * FSM stops, the command is marked as done and added to the history. When the FSM is cancelled, or
+ * if the last execution fails, the effects of the command are undone.
+ * @param factory - Creates the command from the data of the FSM when it starts.
+ * @param update - Updates the command from the data of the FSM before each execution.
+ * @param history - The history that receives the command.
+ */
+pub fn execute_continuously_in<C, T: UndoableCommand + 'static>(
+    mut factory: impl FnMut(&C) -> T + 'static,
+    update: impl FnMut(&mut T, &C) + 'static,
+    history: impl Into<HistoryHandle>,
+) -> Box<FsmObserver<C>> {
+    let history = history.into();
+    let create = move |data: &C| {
+        let mut cmd = Command::new(factory(data));
+        cmd.set_history(history.clone());
+        cmd
+    };
+    continuously(create, update, rollback)
+}
+
+/**
+ * Creates the FSM observer of 'execute_continuously' and 'execute_continuously_in'.
+ * @param create - Creates the command from the data of the FSM when it starts.
+ * @param abort - Receives the command when the FSM is cancelled or its last execution failed.
+ */
+fn continuously<C, T: CustomCmd + 'static>(
+    mut create: impl FnMut(&C) -> Command<T> + 'static,
+    mut update: impl FnMut(&mut T, &C) + 'static,
+    abort: fn(&mut Command<T>),
+) -> Box<FsmObserver<C>> {
+    let mut running: Option<Command<T>> = None;
+    Box::new(move |step, data| match step {
+        FsmStep::Started => running = Some(create(data)),
+        FsmStep::Updated => {
+            if let Some(cmd) = &mut running {
+                update(&mut *cmd.child, data);
+                cmd.execute();
+            }
+        }
+        FsmStep::Stopped => {
+            if let Some(mut cmd) = running.take() {
+                update(&mut *cmd.child, data);
+                if cmd.execute().is_executed() {
+                    cmd.done();
+                } else {
+                    abort(&mut cmd);
+                }
+            }
+        }
+        FsmStep::Cancelled => {
+            if let Some(mut cmd) = running.take() {
+                abort(&mut cmd);
+            }
+        }
+        FsmStep::Reinit => running = None,
+    })
+}
+
+/**
+ * Undoes the effects of an executed command and cancels it.
+ */
+fn rollback<T: UndoableCommand>(cmd: &mut Command<T>) {
+    if cmd.get_status() == CmdStatus::Executed {
+        cmd.child.undo();
+    }
+    cmd.cancel();
+}
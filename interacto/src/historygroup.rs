@@ -0,0 +1,132 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::any::Any;
+
+use crate::undoble::Undoable;
+
+/**
+ * The undoable objects added to a history between the beginning and the end of a group.
+ * They are undone and redone as a single step.
+ */
+pub struct UndoableGroup {
+    name: String,
+    undoables: Vec<Box<dyn Undoable>>,
+}
+
+impl UndoableGroup {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            undoables: Vec::new(),
+        }
+    }
+
+    /**
+     * @returns The undoable objects of the group, in their order of addition.
+     */
+    pub fn get_undoables(&self) -> &[Box<dyn Undoable>] {
+        &self.undoables
+    }
+
+    /**
+     * Rebuilds a saved group (see 'persistence').
+     */
+    #[cfg(feature = "serde")]
+    pub(crate) fn restore(name: String, undoables: Vec<Box<dyn Undoable>>) -> Self {
+        Self { name, undoables }
+    }
+}
+
+impl Undoable for UndoableGroup {
+    fn undo(&mut self) {
+        self.undoables.iter_mut().rev().for_each(|undoable| undoable.undo());
+    }
+
+    fn redo(&mut self) {
+        self.undoables.iter_mut().for_each(|undoable| undoable.redo());
+    }
+
+    fn get_undo_name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn memory_footprint(&self) -> usize {
+        std::mem::size_of_val(self) + self.undoables.iter().map(|undoable| undoable.memory_footprint()).sum::<usize>()
+    }
+
+    fn as_any(&self) -> Option<&dyn Any> {
+        Some(self)
+    }
+}
+
+/**
+ * The groups currently open in a history, from the outermost to the innermost.
+ */
+pub(crate) struct GroupStack {
+    groups: Vec<UndoableGroup>,
+}
+
+impl GroupStack {
+    pub(crate) fn new() -> Self {
+        Self { groups: Vec::new() }
+    }
+
+    pub(crate) fn is_open(&self) -> bool {
+        !self.groups.is_empty()
+    }
+
+    pub(crate) fn begin(&mut self, name: &str) {
+        self.groups.push(UndoableGroup::new(name));
+    }
+
+    /**
+     * Adds the undoable object to the innermost group.
+     * @returns The undoable object back if no group is open.
+     */
+    pub(crate) fn push(&mut self, undoable: Box<dyn Undoable>) -> Option<Box<dyn Undoable>> {
+        match self.groups.last_mut() {
+            Some(group) => {
+                group.undoables.push(undoable);
+                None
+            }
+            None => Some(undoable),
+        }
+    }
+
+    /**
+     * Closes the innermost group. A nested group is added to its enclosing group.
+     * @returns The closed group if it is the outermost one and is not empty, to add it to the history.
+     */
+    pub(crate) fn end(&mut self) -> Option<Box<dyn Undoable>> {
+        let group = self.groups.pop().filter(|group| !group.undoables.is_empty())?;
+        self.push(Box::new(group))
+    }
+
+    /**
+     * Closes the innermost group and undoes its undoable objects, in the reverse order.
+     */
+    pub(crate) fn abandon(&mut self) {
+        if let Some(mut group) = self.groups.pop() {
+            group.undo();
+        }
+    }
+
+    /**
+     * Closes all the groups without undoing them.
+     */
+    pub(crate) fn clear(&mut self) {
+        self.groups.clear();
+    }
+}
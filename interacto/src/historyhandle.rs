@@ -0,0 +1,266 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::{
+    cell::{Cell, Ref, RefCell, RefMut},
+    ops::{Deref, DerefMut},
+    rc::Rc,
+};
+
+use crate::{
+    subject::{Subject, Subscription},
+    undoble::Undoable,
+    undohistory::{UndoHistoryBase, UndoableObserver},
+};
+
+/**
+ * A cloneable handle on an undo history.
+ * All the clones of a handle operate on the same history, so that the Undo and Redo commands
+ * and the code that adds undoable objects can share it.
+ * The handle keeps the concrete type of the history (e.g. `HistoryHandle<LinearHistoryImpl>`),
+ * so that its specific operations remain reachable. It converts into the type-erased
+ * `HistoryHandle` (i.e. `HistoryHandle<dyn UndoHistoryBase>`) that Undo and Redo use.
+ */
+pub struct HistoryHandle<H: ?Sized + UndoHistoryBase = dyn UndoHistoryBase> {
+    history: Rc<RefCell<H>>,
+    publishers: Rc<HandlePublishers>,
+}
+
+/**
+ * The streams of a handle, shared by all its clones.
+ * The history only flags its changes: they are published once the history is no longer
+ * mutably borrowed, so that observers can read the history.
+ */
+struct HandlePublishers {
+    undos: Subject<UndoableObserver>,
+    redos: Subject<UndoableObserver>,
+    undo_changed: Rc<Cell<bool>>,
+    redo_changed: Rc<Cell<bool>>,
+    /**
+     * The subscriptions to the streams of the history, that flag its changes.
+     */
+    _history_subscriptions: [Subscription; 2],
+}
+
+impl<H: UndoHistoryBase + 'static> HistoryHandle<H> {
+    pub fn new(history: H) -> Self {
+        let undo_changed = Rc::new(Cell::new(false));
+        let redo_changed = Rc::new(Cell::new(false));
+        let undo_flag = undo_changed.clone();
+        let redo_flag = redo_changed.clone();
+        let history_subscriptions = [
+            history.subscribe_undos(Box::new(move |_| undo_flag.set(true))),
+            history.subscribe_redos(Box::new(move |_| redo_flag.set(true))),
+        ];
+        Self {
+            history: Rc::new(RefCell::new(history)),
+            publishers: Rc::new(HandlePublishers {
+                undos: Subject::new(),
+                redos: Subject::new(),
+                undo_changed,
+                redo_changed,
+                _history_subscriptions: history_subscriptions,
+            }),
+        }
+    }
+
+    /**
+     * @returns A type-erased handle on the same history.
+     */
+    pub fn erase(&self) -> HistoryHandle {
+        HistoryHandle {
+            history: self.history.clone(),
+            publishers: self.publishers.clone(),
+        }
+    }
+}
+
+impl<H: UndoHistoryBase + 'static> From<HistoryHandle<H>> for HistoryHandle {
+    fn from(handle: HistoryHandle<H>) -> Self {
+        HistoryHandle {
+            history: handle.history,
+            publishers: handle.publishers,
+        }
+    }
+}
+
+impl<H: ?Sized + UndoHistoryBase> Clone for HistoryHandle<H> {
+    fn clone(&self) -> Self {
+        Self {
+            history: self.history.clone(),
+            publishers: self.publishers.clone(),
+        }
+    }
+}
+
+impl<H: ?Sized + UndoHistoryBase> HistoryHandle<H> {
+    /**
+     * Borrows the history.
+     * Panics if the history is currently mutably borrowed.
+     */
+    pub fn borrow(&self) -> Ref<'_, H> {
+        self.history.borrow()
+    }
+
+    /**
+     * Mutably borrows the history.
+     * The changes of the last undoable and redoable objects are published to the observers of the
+     * handle when the returned borrow is dropped.
+     * Panics if the history is currently borrowed.
+     */
+    pub fn borrow_mut(&self) -> HistoryRefMut<'_, H> {
+        HistoryRefMut {
+            history: Some(self.history.borrow_mut()),
+            handle: self,
+        }
+    }
+
+    /**
+     * Undoes the last undoable object of the history.
+     */
+    pub fn undo(&self) {
+        self.borrow_mut().undo()
+    }
+
+    /**
+     * Redoes the last redoable object of the history.
+     */
+    pub fn redo(&self) {
+        self.borrow_mut().redo()
+    }
+
+    /**
+     * Removes all the undoable objects of the history.
+     */
+    pub fn clear(&self) {
+        self.borrow_mut().clear()
+    }
+
+    /**
+     * Adds an undoable object to the history.
+     * @param undoable - The undoable object to add.
+     */
+    pub fn add(&self, undoable: Box<dyn Undoable>) {
+        self.borrow_mut().add(undoable)
+    }
+
+    /**
+     * Starts a group of undoable objects that form a single undo step.
+     * @param name - The undo name of the group.
+     */
+    pub fn begin_group(&self, name: &str) {
+        self.borrow_mut().begin_group(name)
+    }
+
+    /**
+     * Ends the innermost group and adds it to the history.
+     */
+    pub fn end_group(&self) {
+        self.borrow_mut().end_group()
+    }
+
+    /**
+     * Ends the innermost group by undoing its undoable objects.
+     */
+    pub fn abandon_group(&self) {
+        self.borrow_mut().abandon_group()
+    }
+
+    /**
+     * @returns True if the history contains an object to undo.
+     */
+    pub fn can_undo(&self) -> bool {
+        self.borrow().get_last_undo().is_some()
+    }
+
+    /**
+     * @returns True if the history contains an object to redo.
+     */
+    pub fn can_redo(&self) -> bool {
+        self.borrow().get_last_redo().is_some()
+    }
+
+    /**
+     * Observes the changes regarding the last undoable object of the history.
+     * Observers are notified once the history is no longer mutably borrowed, so they can read it
+     * through the handle. They must not modify it.
+     * @returns The subscription of the observer: the observer is removed when it is dropped.
+     */
+    pub fn subscribe_undos(&self, observer: impl FnMut(Option<&dyn Undoable>) + 'static) -> Subscription {
+        self.publishers.undos.subscribe(Box::new(observer))
+    }
+
+    /**
+     * Observes the changes regarding the last redoable object of the history.
+     * Observers are notified once the history is no longer mutably borrowed, so they can read it
+     * through the handle. They must not modify it.
+     * @returns The subscription of the observer: the observer is removed when it is dropped.
+     */
+    pub fn subscribe_redos(&self, observer: impl FnMut(Option<&dyn Undoable>) + 'static) -> Subscription {
+        self.publishers.redos.subscribe(Box::new(observer))
+    }
+
+    /**
+     * Publishes the pending changes of the history to the observers of the handle.
+     */
+    fn publish(&self) {
+        if self.publishers.undo_changed.replace(false) {
+            let history = self.borrow();
+            let last = history.get_last_undo();
+            self.publishers.undos.notify(|observer| observer(last));
+        }
+        if self.publishers.redo_changed.replace(false) {
+            let history = self.borrow();
+            let last = history.get_last_redo();
+            self.publishers.redos.notify(|observer| observer(last));
+        }
+    }
+
+    /**
+     * @returns True if both handles operate on the same history.
+     */
+    pub fn ptr_eq<G: ?Sized + UndoHistoryBase>(&self, other: &HistoryHandle<G>) -> bool {
+        std::ptr::addr_eq(Rc::as_ptr(&self.history), Rc::as_ptr(&other.history))
+    }
+}
+
+/**
+ * A mutable borrow of the history of a handle (see 'HistoryHandle::borrow_mut').
+ * Once released, the changes of the history are published to the observers of the handle.
+ */
+pub struct HistoryRefMut<'a, H: ?Sized + UndoHistoryBase> {
+    history: Option<RefMut<'a, H>>,
+    handle: &'a HistoryHandle<H>,
+}
+
+impl<H: ?Sized + UndoHistoryBase> Deref for HistoryRefMut<'_, H> {
+    type Target = H;
+
+    fn deref(&self) -> &H {
+        self.history.as_deref().expect("the history is borrowed until dropped")
+    }
+}
+
+impl<H: ?Sized + UndoHistoryBase> DerefMut for HistoryRefMut<'_, H> {
+    fn deref_mut(&mut self) -> &mut H {
+        self.history.as_deref_mut().expect("the history is borrowed until dropped")
+    }
+}
+
+impl<H: ?Sized + UndoHistoryBase> Drop for HistoryRefMut<'_, H> {
+    fn drop(&mut self) {
+        self.history = None;
+        self.handle.publish();
+    }
+}
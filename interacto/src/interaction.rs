@@ -0,0 +1,241 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use crate::{
+    clock::Clock,
+    event::{Modifiers, MouseButton, PointerEvent, UIEvent, WidgetId},
+    fsm::{Fsm, FsmObserver},
+    subject::Subscription,
+};
+
+/**
+ * Implements 'Interaction' for a type that owns its FSM in a 'fsm' field.
+ * With the 'Default' marker, also implements 'Default' through the 'new' constructor.
+ */
+macro_rules! fsm_interaction {
+    ($interaction:ty, $data:ty) => {
+        impl $crate::interaction::Interaction for $interaction {
+            type Data = $data;
+
+            fn get_fsm(&self) -> &$crate::fsm::Fsm<$crate::event::UIEvent, $data> {
+                &self.fsm
+            }
+
+            fn get_fsm_mut(&mut self) -> &mut $crate::fsm::Fsm<$crate::event::UIEvent, $data> {
+                &mut self.fsm
+            }
+        }
+    };
+    ($interaction:ty, $data:ty, Default) => {
+        $crate::interaction::fsm_interaction!($interaction, $data);
+
+        impl Default for $interaction {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+    };
+}
+
+pub(crate) use fsm_interaction;
+
+/**
+ * A user interaction: an FSM fed with UI events, that builds typed data while it runs.
+ * Observers of the interaction receive its data on each step, to create and update commands
+ * (see 'fsm::execute_on_stop').
+ */
+pub trait Interaction {
+    type Data: Default + 'static;
+
+    fn get_fsm(&self) -> &Fsm<UIEvent, Self::Data>;
+
+    fn get_fsm_mut(&mut self) -> &mut Fsm<UIEvent, Self::Data>;
+
+    /**
+     * Processes a UI event.
+     * @returns True if the event made the interaction progress.
+     */
+    fn process_event(&mut self, event: &UIEvent) -> bool {
+        self.get_fsm_mut().process(event)
+    }
+
+    /**
+     * Fires the pending timeout of the interaction, if expired.
+     * @returns True if the timeout made the interaction progress.
+     */
+    fn check_timeout(&mut self) -> bool {
+        self.get_fsm_mut().check_timeout()
+    }
+
+    /**
+     * @returns The data of the running interaction. Reset when the interaction ends.
+     */
+    fn get_data(&self) -> &Self::Data {
+        self.get_fsm().get_data()
+    }
+
+    /**
+     * @returns True if the interaction started and is not finished.
+     */
+    fn is_running(&self) -> bool {
+        self.get_fsm().is_started()
+    }
+
+    /**
+     * Aborts the interaction.
+     */
+    fn cancel(&mut self) {
+        self.get_fsm_mut().cancel()
+    }
+
+    /**
+     * Observes the steps of the interaction with its data.
+     * @returns The subscription of the observer.
+     */
+    fn subscribe(&self, observer: Box<FsmObserver<Self::Data>>) -> Subscription {
+        self.get_fsm().subscribe(observer)
+    }
+
+    /**
+     * Replaces the clock used by the timeouts of the interaction.
+     */
+    fn set_clock(&mut self, clock: Box<dyn Clock>) {
+        self.get_fsm_mut().set_clock(clock)
+    }
+}
+
+/**
+ * The data of an interaction made of a single pointer event.
+ */
+#[derive(PartialEq, Clone, Copy, Debug, Default)]
+pub struct PointData {
+    pub x: f64,
+    pub y: f64,
+    pub button: MouseButton,
+    pub modifiers: Modifiers,
+    pub target: Option<WidgetId>,
+}
+
+impl From<&PointerEvent> for PointData {
+    fn from(event: &PointerEvent) -> Self {
+        Self {
+            x: event.x,
+            y: event.y,
+            button: event.button,
+            modifiers: event.modifiers,
+            target: event.target,
+        }
+    }
+}
+
+/**
+ * The data of an interaction that moves the pointer from a source point to a target point.
+ */
+#[derive(PartialEq, Clone, Copy, Debug, Default)]
+pub struct SrcTgtPointsData {
+    /**
+     * Where the interaction started.
+     */
+    pub src: PointData,
+    /**
+     * The last position of the pointer.
+     */
+    pub tgt: PointData,
+}
+
+impl SrcTgtPointsData {
+    /**
+     * @returns The horizontal move from the source point to the target point.
+     */
+    pub fn get_diff_x(&self) -> f64 {
+        self.tgt.x - self.src.x
+    }
+
+    /**
+     * @returns The vertical move from the source point to the target point.
+     */
+    pub fn get_diff_y(&self) -> f64 {
+        self.tgt.y - self.src.y
+    }
+
+    /**
+     * @returns The modifier keys pressed during the last pointer event.
+     */
+    pub fn get_modifiers(&self) -> Modifiers {
+        self.tgt.modifiers
+    }
+}
+
+/**
+ * @returns True if the event is a press of a pointer button.
+ */
+pub(crate) fn is_pointer_down(event: &UIEvent) -> bool {
+    matches!(event, UIEvent::PointerDown(_))
+}
+
+/**
+ * @returns True if the event is a release of the given pointer button.
+ */
+pub(crate) fn is_pointer_up(event: &UIEvent, button: MouseButton) -> bool {
+    matches!(event, UIEvent::PointerUp(pointer) if pointer.button == button)
+}
+
+/**
+ * @returns True if the event is a move of the pointer.
+ */
+pub(crate) fn is_pointer_move(event: &UIEvent) -> bool {
+    matches!(event, UIEvent::PointerMove(_))
+}
+
+/**
+ * How far, in pixels, the pointer can move from a press without cancelling the click that press starts.
+ * It absorbs the jitter of pointing devices.
+ */
+pub const CLICK_TOLERANCE: f64 = 3.0;
+
+/**
+ * @returns True if the event is a move of the pointer farther than 'CLICK_TOLERANCE' from the given point.
+ */
+pub(crate) fn is_pointer_away(event: &UIEvent, from: &PointData) -> bool {
+    matches!(event, UIEvent::PointerMove(pointer)
+        if (pointer.x - from.x).hypot(pointer.y - from.y) > CLICK_TOLERANCE)
+}
+
+/**
+ * @returns True if the event is a move of the pointer while the given button is held down.
+ */
+pub(crate) fn is_pointer_drag(event: &UIEvent, button: MouseButton) -> bool {
+    matches!(event, UIEvent::PointerMove(pointer) if pointer.buttons.contains(button))
+}
+
+/**
+ * @returns True if the event is a move of the pointer while no button is held down.
+ */
+pub(crate) fn is_pointer_hover(event: &UIEvent) -> bool {
+    matches!(event, UIEvent::PointerMove(pointer) if pointer.buttons.is_empty())
+}
+
+/**
+ * @returns The data of the pointer event, if the event is one.
+ */
+pub(crate) fn point_data(event: &UIEvent) -> Option<PointData> {
+    event.as_pointer().map(PointData::from)
+}
+
+/**
+ * @returns True if the event is a press of the Escape key.
+ */
+pub(crate) fn is_escape(event: &UIEvent) -> bool {
+    matches!(event, UIEvent::KeyDown(key) if key.is_escape())
+}
@@ -12,11 +12,37 @@
  * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
  */
 pub mod command;
+pub mod asynccmd;
 pub mod undoble;
 pub mod anoncmd;
+pub mod macrocmd;
+pub mod memento;
 pub mod undohistory;
+pub mod messageformatter;
+pub mod mergepolicy;
+pub mod historygroup;
 pub mod undo;
 pub mod linearhistory;
+pub mod treehistory;
+pub mod historyhandle;
+pub mod subject;
+pub mod registry;
+pub mod event;
+pub mod clock;
+pub mod fsm;
+pub mod interaction;
+pub mod press;
+pub mod click;
+pub mod doubleclick;
+pub mod longmousedown;
+pub mod clicks;
+pub mod dnd;
+pub mod draglock;
+#[cfg(feature = "serde")]
+pub mod persistence;
+
+#[cfg(feature = "derive")]
+pub use interacto_derive::{Command, Undoable};
 
 #[cfg(test)]
 mod test;
@@ -12,7 +12,15 @@
  * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-use crate::{undoble::Undoable, undohistory::{UndoHistory, UndoHistoryBase}};
+use crate::{
+    clock::Clock,
+    historygroup::GroupStack,
+    mergepolicy::{Coalescer, MergePolicy},
+    messageformatter::{MessageFormatter, NameFormatter},
+    subject::{Subject, Subscription},
+    undoble::Undoable,
+    undohistory::{UndoHistory, UndoHistoryBase, UndoableObserver},
+};
 
 pub struct LinearHistoryImpl {
     /**
@@ -29,61 +37,232 @@ pub struct LinearHistoryImpl {
      * The maximal number of undo.
      */
     size_max: u64,
-    // private readonly undoPublisher: Subject<Undoable | undefined>;
 
-    // private readonly redoPublisher: Subject<Undoable | undefined>;
+    /**
+     * The maximal number of bytes the undoable objects can hold.
+     */
+    memory_max: Option<usize>,
+
+    undo_publisher: Subject<UndoableObserver>,
+
+    redo_publisher: Subject<UndoableObserver>,
+
+    /**
+     * Formats the undo and redo messages.
+     */
+    formatter: Box<dyn MessageFormatter>,
+
+    /**
+     * Merges the added undoable objects according to the merge policy.
+     */
+    coalescer: Coalescer,
+
+    /**
+     * The groups being built.
+     */
+    groups: GroupStack,
 }
 
 impl LinearHistoryImpl {
     pub fn new() -> Self {
         Self {
             size_max: 100,
+            memory_max: None,
             undos: Vec::new(),
             redos: Vec::new(),
+            undo_publisher: Subject::new(),
+            redo_publisher: Subject::new(),
+            formatter: Box::new(NameFormatter),
+            coalescer: Coalescer::new(),
+            groups: GroupStack::new(),
         }
     }
+
+    /**
+     * Removes the oldest undoable objects until the memory budget is met, keeping the last one.
+     */
+    fn evict_to_memory_max(&mut self) {
+        if let Some(max) = self.memory_max {
+            let mut footprint = self.get_memory_footprint();
+            let mut count = 0;
+            while footprint > max && count + 1 < self.undos.len() {
+                footprint -= self.undos[count].memory_footprint();
+                count += 1;
+            }
+            self.undos.drain(0..count);
+        }
+    }
+
+    fn publish_undo(&self) {
+        let last = self.get_last_undo();
+        self.undo_publisher.notify(|observer| observer(last));
+    }
+
+    fn publish_redo(&self) {
+        let last = self.get_last_redo();
+        self.redo_publisher.notify(|observer| observer(last));
+    }
+}
+
+impl Default for LinearHistoryImpl {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl UndoHistoryBase for LinearHistoryImpl {
-    fn undo(&self) {
-        todo!()
+    fn undo(&mut self) {
+        if let Some(mut undoable) = self.undos.pop() {
+            self.coalescer.reset();
+            undoable.undo();
+            self.redos.push(undoable);
+            self.publish_undo();
+            self.publish_redo();
+        }
     }
 
-    fn redo(&self) {
-        todo!()
+    fn redo(&mut self) {
+        if let Some(mut undoable) = self.redos.pop() {
+            self.coalescer.reset();
+            undoable.redo();
+            self.undos.push(undoable);
+            self.publish_undo();
+            self.publish_redo();
+        }
     }
 
-    fn clear(&self) {
-        todo!()
+    fn clear(&mut self) {
+        self.groups.clear();
+        self.coalescer.reset();
+        if !self.undos.is_empty() {
+            self.undos.clear();
+            self.publish_undo();
+        }
+        if !self.redos.is_empty() {
+            self.redos.clear();
+            self.publish_redo();
+        }
     }
 
-    fn add(&self, undoable: Box<dyn Undoable>) {
-        todo!()
+    fn add(&mut self, undoable: Box<dyn Undoable>) {
+        let Some(undoable) = self.groups.push(undoable) else {
+            return;
+        };
+        if self.size_max > 0 {
+            if !self.coalescer.merge(self.undos.last_mut(), undoable.as_ref()) {
+                if self.undos.len() as u64 == self.size_max {
+                    self.undos.remove(0);
+                }
+                self.undos.push(undoable);
+            }
+            self.evict_to_memory_max();
+            self.publish_undo();
+            if !self.redos.is_empty() {
+                self.redos.clear();
+                self.publish_redo();
+            }
+        }
+    }
+
+    fn begin_group(&mut self, name: &str) {
+        self.groups.begin(name);
+    }
+
+    fn end_group(&mut self) {
+        if let Some(group) = self.groups.end() {
+            self.add(group);
+        }
+    }
+
+    fn abandon_group(&mut self) {
+        self.groups.abandon();
+    }
+
+    fn is_grouping(&self) -> bool {
+        self.groups.is_open()
+    }
+
+    fn get_merge_policy(&self) -> MergePolicy {
+        self.coalescer.get_policy()
+    }
+
+    fn set_merge_policy(&mut self, policy: MergePolicy) {
+        self.coalescer.set_policy(policy);
+    }
+
+    fn set_clock(&mut self, clock: Box<dyn Clock>) {
+        self.coalescer.set_clock(clock);
     }
 
     fn get_last_undo(&self) -> Option<&dyn Undoable> {
-        todo!()
+        self.undos.last().map(|undoable| undoable.as_ref())
+    }
+
+    fn get_last_redo(&self) -> Option<&dyn Undoable> {
+        self.redos.last().map(|undoable| undoable.as_ref())
+    }
+
+    fn subscribe_undos(&self, observer: Box<UndoableObserver>) -> Subscription {
+        self.undo_publisher.subscribe(observer)
+    }
+
+    fn subscribe_redos(&self, observer: Box<UndoableObserver>) -> Subscription {
+        self.redo_publisher.subscribe(observer)
+    }
+
+    fn get_message_formatter(&self) -> &dyn MessageFormatter {
+        self.formatter.as_ref()
     }
 
-    fn get_last_redo(&self) -> Option<Box<dyn Undoable>> {
-        todo!()
+    fn set_message_formatter(&mut self, formatter: Box<dyn MessageFormatter>) {
+        self.formatter = formatter;
     }
 }
 
 impl UndoHistory for LinearHistoryImpl {
-    fn get_undo(&self) -> Vec<Box<dyn Undoable>> {
-        todo!()
+    fn get_undo(&self) -> &[Box<dyn Undoable>] {
+        &self.undos
     }
 
-    fn get_redo(&self) -> Vec<Box<dyn Undoable>> {
-        todo!()
+    fn get_redo(&self) -> &[Box<dyn Undoable>] {
+        &self.redos
+    }
+
+    fn restore(&mut self, undos: Vec<Box<dyn Undoable>>, redos: Vec<Box<dyn Undoable>>) {
+        self.groups.clear();
+        self.coalescer.reset();
+        self.undos = undos;
+        self.redos = redos;
+        let len = self.undos.len() as u64;
+        if len > self.size_max {
+            self.undos.drain(0..(len - self.size_max) as usize);
+        }
+        self.evict_to_memory_max();
+        self.publish_undo();
+        self.publish_redo();
     }
 
     fn get_size_max(&self) -> u64 {
-        todo!()
+        self.size_max
     }
 
     fn set_size_max(&mut self, max: u64) {
-        todo!()
+        let len = self.undos.len() as u64;
+        if len > max {
+            self.undos.drain(0..(len - max) as usize);
+            if self.undos.is_empty() {
+                self.publish_undo();
+            }
+        }
+        self.size_max = max;
+    }
+
+    fn get_memory_max(&self) -> Option<usize> {
+        self.memory_max
+    }
+
+    fn set_memory_max(&mut self, max: Option<usize>) {
+        self.memory_max = max;
+        self.evict_to_memory_max();
     }
 }
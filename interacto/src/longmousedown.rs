@@ -0,0 +1,55 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::time::Duration;
+
+use crate::{
+    event::UIEvent,
+    fsm::Fsm,
+    interaction::{fsm_interaction, is_pointer_down, point_data, PointData},
+};
+
+/**
+ * A pointer button kept pressed for a given duration.
+ * The interaction stops when the duration elapses; releasing the button before cancels it.
+ * Its data is the one of the press.
+ */
+pub struct LongMouseDown {
+    fsm: Fsm<UIEvent, PointData>,
+    duration: Duration,
+}
+
+impl LongMouseDown {
+    /**
+     * @param duration - How long the button must be pressed.
+     */
+    pub fn new(duration: Duration) -> Self {
+        let mut fsm: Fsm<UIEvent, PointData> = Fsm::new();
+        let init = fsm.get_init_state();
+        let pressed = fsm.add_std_state("pressed");
+        let held = fsm.add_terminal_state("held");
+        let released = fsm.add_cancelling_state("released");
+        fsm.add_transition(init, pressed, is_pointer_down)
+            .with_action(|event, data| *data = point_data(event).unwrap_or_default());
+        fsm.add_transition(pressed, released, |event| matches!(event, UIEvent::PointerUp(_)));
+        fsm.add_timeout_transition(pressed, held, duration);
+        Self { fsm, duration }
+    }
+
+    pub fn get_duration(&self) -> Duration {
+        self.duration
+    }
+}
+
+fsm_interaction!(LongMouseDown, PointData);
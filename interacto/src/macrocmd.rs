@@ -0,0 +1,273 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::{any::Any, error::Error, fmt};
+
+use crate::{command::{CommandBase, CustomCmd}, undoble::Undoable};
+
+/**
+ * A command that can be part of a macro command.
+ * It is implemented by all the undoable commands whose errors can be boxed.
+ */
+pub trait MacroChild: Undoable {
+    fn can_execute(&self) -> bool;
+
+    fn create_memento(&mut self);
+
+    /**
+     * Executes the command.
+     * @returns The error of the command if its execution failed.
+     */
+    fn execute(&mut self) -> Result<(), Box<dyn Error>>;
+}
+
+impl<T> MacroChild for T
+where
+    T: CustomCmd + Undoable,
+    T::Error: Into<Box<dyn Error>>,
+{
+    fn can_execute(&self) -> bool {
+        CommandBase::can_execute(self)
+    }
+
+    fn create_memento(&mut self) {
+        CommandBase::create_memento(self)
+    }
+
+    fn execute(&mut self) -> Result<(), Box<dyn Error>> {
+        self.execution().map_err(Into::into)
+    }
+}
+
+/**
+ * The error produced when a command of a macro command fails.
+ */
+#[derive(Debug)]
+pub struct MacroCmdError {
+    /**
+     * The position of the failing command in the macro command.
+     */
+    pub index: usize,
+    pub source: Box<dyn Error>,
+}
+
+impl fmt::Display for MacroCmdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "command {} of the macro command failed: {}", self.index, self.source)
+    }
+}
+
+impl Error for MacroCmdError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(self.source.as_ref())
+    }
+}
+
+/**
+ * A command made of several commands executed in sequence as a single user action.
+ * If one of its commands fails, the commands already executed are undone.
+ * Undoing a macro command undoes its commands in the reverse order.
+ */
+pub struct MacroCmd {
+    name: Option<String>,
+    cmds: Vec<Box<dyn MacroChild>>,
+    /**
+     * Whether the mementos of the commands must be created before executing them.
+     */
+    mementos_pending: bool,
+}
+
+impl MacroCmd {
+    pub fn new() -> Self {
+        Self {
+            name: None,
+            cmds: Vec::new(),
+            mementos_pending: false,
+        }
+    }
+
+    /**
+     * @param name - The undo name of the macro command. By default, the names of its commands are combined.
+     */
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /**
+     * Adds a command at the end of the macro command.
+     */
+    pub fn with(mut self, cmd: impl MacroChild + 'static) -> Self {
+        self.add(cmd);
+        self
+    }
+
+    /**
+     * Adds a command at the end of the macro command.
+     */
+    pub fn add(&mut self, cmd: impl MacroChild + 'static) {
+        self.cmds.push(Box::new(cmd));
+    }
+
+    pub fn len(&self) -> usize {
+        self.cmds.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cmds.is_empty()
+    }
+
+    /**
+     * @returns The undo name given to the macro command, if any (see 'with_name').
+     */
+    pub fn get_name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /**
+     * @returns The commands of the macro command, in their order of execution.
+     */
+    pub fn get_cmds(&self) -> &[Box<dyn MacroChild>] {
+        &self.cmds
+    }
+
+    /**
+     * Rebuilds a saved macro command (see 'persistence'): its commands can be undone and redone,
+     * but not executed again.
+     */
+    #[cfg(feature = "serde")]
+    pub(crate) fn restore(name: Option<String>, undoables: Vec<Box<dyn Undoable>>) -> Self {
+        Self {
+            name,
+            cmds: undoables
+                .into_iter()
+                .map(|undoable| Box::new(RestoredChild(undoable)) as Box<dyn MacroChild>)
+                .collect(),
+            mementos_pending: false,
+        }
+    }
+}
+
+/**
+ * A command of a restored macro command.
+ */
+#[cfg(feature = "serde")]
+struct RestoredChild(Box<dyn Undoable>);
+
+#[cfg(feature = "serde")]
+impl Undoable for RestoredChild {
+    fn undo(&mut self) {
+        self.0.undo()
+    }
+
+    fn redo(&mut self) {
+        self.0.redo()
+    }
+
+    fn get_undo_name(&self) -> String {
+        self.0.get_undo_name()
+    }
+
+    fn visual_snapshot(&self) -> Option<crate::undoble::UndoableSnapshot> {
+        self.0.visual_snapshot()
+    }
+
+    fn memory_footprint(&self) -> usize {
+        self.0.memory_footprint()
+    }
+
+    fn as_any(&self) -> Option<&dyn Any> {
+        self.0.as_any()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl MacroChild for RestoredChild {
+    fn can_execute(&self) -> bool {
+        false
+    }
+
+    fn create_memento(&mut self) {}
+
+    fn execute(&mut self) -> Result<(), Box<dyn Error>> {
+        Err("a restored command cannot be executed again".into())
+    }
+}
+
+impl Default for MacroCmd {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CommandBase for MacroCmd {
+    fn create_memento(&mut self) {
+        self.mementos_pending = true;
+    }
+
+    /**
+     * A macro command can be executed if it is not empty and if all its commands can be executed.
+     */
+    fn can_execute(&self) -> bool {
+        !self.cmds.is_empty() && self.cmds.iter().all(|cmd| cmd.can_execute())
+    }
+}
+
+impl CustomCmd for MacroCmd {
+    type Error = MacroCmdError;
+
+    fn execution(&mut self) -> Result<(), Self::Error> {
+        for index in 0..self.cmds.len() {
+            if self.mementos_pending {
+                self.cmds[index].create_memento();
+            }
+            if let Err(source) = self.cmds[index].execute() {
+                self.cmds[..index].iter_mut().rev().for_each(|cmd| cmd.undo());
+                return Err(MacroCmdError { index, source });
+            }
+        }
+        self.mementos_pending = false;
+        Ok(())
+    }
+}
+
+impl Undoable for MacroCmd {
+    fn undo(&mut self) {
+        self.cmds.iter_mut().rev().for_each(|cmd| cmd.undo());
+    }
+
+    fn redo(&mut self) {
+        self.cmds.iter_mut().for_each(|cmd| cmd.redo());
+    }
+
+    fn get_undo_name(&self) -> String {
+        match &self.name {
+            Some(name) => name.clone(),
+            None => self
+                .cmds
+                .iter()
+                .map(|cmd| cmd.get_undo_name())
+                .collect::<Vec<String>>()
+                .join(", "),
+        }
+    }
+
+    fn memory_footprint(&self) -> usize {
+        std::mem::size_of_val(self) + self.cmds.iter().map(|cmd| cmd.memory_footprint()).sum::<usize>()
+    }
+
+    fn as_any(&self) -> Option<&dyn Any> {
+        Some(self)
+    }
+}
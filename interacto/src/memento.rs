@@ -0,0 +1,207 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{command::{CommandBase, CustomCmd}, undoble::Undoable};
+
+/**
+ * Captures a state before the first execution of a command, to restore it on undo.
+ * The captured state can be a whole model or only the part of it the command changes.
+ */
+#[derive(Clone, Debug)]
+pub struct Memento<S: Clone> {
+    /**
+     * The state before the first execution.
+     */
+    before: Option<S>,
+
+    /**
+     * The state before the last undo, restored on redo.
+     */
+    after: Option<S>,
+}
+
+impl<S: Clone> Memento<S> {
+    pub fn new() -> Self {
+        Self {
+            before: None,
+            after: None,
+        }
+    }
+
+    /**
+     * Captures the state. Only the first capture is kept: the next ones are ignored.
+     * @returns True if the state has been captured.
+     */
+    pub fn capture(&mut self, state: &S) -> bool {
+        if self.before.is_some() {
+            return false;
+        }
+        self.before = Some(state.clone());
+        true
+    }
+
+    /**
+     * @returns True if a state has been captured.
+     */
+    pub fn is_captured(&self) -> bool {
+        self.before.is_some()
+    }
+
+    /**
+     * @returns The captured state, if any.
+     */
+    pub fn get_state(&self) -> Option<&S> {
+        self.before.as_ref()
+    }
+
+    /**
+     * Restores the captured state, for undoing. The current state is kept for redoing.
+     * Does nothing if no state has been captured.
+     */
+    pub fn restore(&mut self, state: &mut S) {
+        if let Some(before) = &self.before {
+            self.after = Some(std::mem::replace(state, before.clone()));
+        }
+    }
+
+    /**
+     * Restores the state replaced by the last 'restore', for redoing.
+     */
+    pub fn reapply(&mut self, state: &mut S) {
+        if let Some(after) = self.after.take() {
+            *state = after;
+        }
+    }
+
+    /**
+     * @param state_footprint - Gives the number of bytes a state holds, including its heap data.
+     * @returns The number of bytes the captured states hold.
+     */
+    pub fn memory_footprint(&self, state_footprint: impl Fn(&S) -> usize) -> usize {
+        self.before.iter().chain(self.after.iter()).map(state_footprint).sum()
+    }
+}
+
+impl<S: Clone> Default for Memento<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/**
+ * A change of a model that can be cloned.
+ * Wrapped in a 'ModelCmd', it becomes an undoable command: the model is captured before the
+ * first execution and restored on undo.
+ */
+pub trait ModelChange {
+    type Model: Clone;
+
+    type Error;
+
+    /**
+     * @returns True if the change can be applied to the model.
+     */
+    fn can_apply(&self, _model: &Self::Model) -> bool {
+        true
+    }
+
+    /**
+     * Applies the change to the model.
+     * @returns An error if the change failed.
+     */
+    fn apply(&mut self, model: &mut Self::Model) -> Result<(), Self::Error>;
+
+    /**
+     * @returns The name of the change, used as undo name.
+     */
+    fn get_undo_name(&self) -> String;
+
+    /**
+     * @returns The number of bytes a captured model holds (see 'Undoable::memory_footprint').
+     * By default, the size of the model itself: models that own heap data should override it
+     * to include that data.
+     */
+    fn model_footprint(&self, model: &Self::Model) -> usize {
+        std::mem::size_of_val(model)
+    }
+}
+
+/**
+ * An undoable command that applies a change to a shared model.
+ */
+pub struct ModelCmd<C: ModelChange> {
+    change: C,
+    model: Rc<RefCell<C::Model>>,
+    memento: Memento<C::Model>,
+}
+
+impl<C: ModelChange> ModelCmd<C> {
+    pub fn new(model: Rc<RefCell<C::Model>>, change: C) -> Self {
+        Self {
+            change,
+            model,
+            memento: Memento::new(),
+        }
+    }
+
+    pub fn get_change(&self) -> &C {
+        &self.change
+    }
+
+    pub fn get_model(&self) -> &Rc<RefCell<C::Model>> {
+        &self.model
+    }
+
+    pub fn get_memento(&self) -> &Memento<C::Model> {
+        &self.memento
+    }
+}
+
+impl<C: ModelChange> CommandBase for ModelCmd<C> {
+    fn create_memento(&mut self) {
+        self.memento.capture(&self.model.borrow());
+    }
+
+    fn can_execute(&self) -> bool {
+        self.change.can_apply(&self.model.borrow())
+    }
+}
+
+impl<C: ModelChange> CustomCmd for ModelCmd<C> {
+    type Error = C::Error;
+
+    fn execution(&mut self) -> Result<(), Self::Error> {
+        self.change.apply(&mut self.model.borrow_mut())
+    }
+}
+
+impl<C: ModelChange> Undoable for ModelCmd<C> {
+    fn undo(&mut self) {
+        self.memento.restore(&mut self.model.borrow_mut());
+    }
+
+    fn redo(&mut self) {
+        self.memento.reapply(&mut self.model.borrow_mut());
+    }
+
+    fn get_undo_name(&self) -> String {
+        self.change.get_undo_name()
+    }
+
+    fn memory_footprint(&self) -> usize {
+        std::mem::size_of_val(self) + self.memento.memory_footprint(|model| self.change.model_footprint(model))
+    }
+}
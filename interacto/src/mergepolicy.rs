@@ -0,0 +1,103 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::time::Duration;
+
+use crate::{
+    clock::{Clock, SystemClock},
+    undoble::Undoable,
+};
+
+/**
+ * Defines when a history tries to merge an added undoable object into the previous one
+ * (see 'Undoable::merge_with'). Histories use 'Never' until another policy is set.
+ */
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum MergePolicy {
+    /** Undoable objects are never merged. */
+    Never,
+    /** Consecutive undoable objects are always candidates for merging. */
+    Always,
+    /** Consecutive undoable objects are candidates for merging if they have the same undo name. */
+    SameName,
+    /** Undoable objects are candidates for merging if they are added within the given delay after the previous one. */
+    Within(Duration),
+}
+
+/**
+ * Applies a merge policy to the undoable objects added to a history.
+ */
+pub(crate) struct Coalescer {
+    policy: MergePolicy,
+    /**
+     * When the previous undoable object has been added, or none if the next one must not be merged.
+     */
+    last_add: Option<Duration>,
+    /**
+     * Gives the time at which undoable objects are added, for the 'Within' policy.
+     */
+    clock: Box<dyn Clock>,
+}
+
+impl Coalescer {
+    pub(crate) fn new() -> Self {
+        Self {
+            policy: MergePolicy::Never,
+            last_add: None,
+            clock: Box::new(SystemClock::new()),
+        }
+    }
+
+    pub(crate) fn get_policy(&self) -> MergePolicy {
+        self.policy
+    }
+
+    pub(crate) fn set_policy(&mut self, policy: MergePolicy) {
+        self.policy = policy;
+        self.last_add = None;
+    }
+
+    pub(crate) fn set_clock(&mut self, clock: Box<dyn Clock>) {
+        self.clock = clock;
+        self.last_add = None;
+    }
+
+    /**
+     * Prevents the next added undoable object from being merged, for example after an undo.
+     */
+    pub(crate) fn reset(&mut self) {
+        self.last_add = None;
+    }
+
+    /**
+     * Tries to merge the added undoable object into the previous one, according to the policy.
+     * @param previous - The last undoable object of the history, if it can receive merges.
+     * @param next - The added undoable object.
+     * @returns True if the object has been merged.
+     */
+    pub(crate) fn merge(&mut self, previous: Option<&mut Box<dyn Undoable>>, next: &dyn Undoable) -> bool {
+        let now = self.clock.now();
+        let last_add = self.last_add.replace(now);
+        let previous = match (previous, last_add) {
+            (Some(previous), Some(last_add)) => match self.policy {
+                MergePolicy::Never => None,
+                MergePolicy::Always => Some(previous),
+                MergePolicy::SameName => Some(previous).filter(|p| p.get_undo_name() == next.get_undo_name()),
+                MergePolicy::Within(delay) => Some(previous).filter(|_| now.saturating_sub(last_add) < delay),
+            },
+            _ => None,
+        };
+        previous.is_some_and(|previous| previous.merge_with(next))
+    }
+}
@@ -0,0 +1,47 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+/**
+ * Formats the names of undoable objects into the messages shown to the users,
+ * for example to translate them or to prefix them with the action ("Undo Move 3 shapes").
+ */
+pub trait MessageFormatter {
+    /**
+     * @param name - The name of the undoable object to undo.
+     * @returns The message to show for undoing the object.
+     */
+    fn format_undo(&self, name: &str) -> String;
+
+    /**
+     * @param name - The name of the undoable object to redo.
+     * @returns The message to show for redoing the object.
+     */
+    fn format_redo(&self, name: &str) -> String;
+}
+
+/**
+ * The default message formatter: messages are the names of the undoable objects.
+ */
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NameFormatter;
+
+impl MessageFormatter for NameFormatter {
+    fn format_undo(&self, name: &str) -> String {
+        name.to_string()
+    }
+
+    fn format_redo(&self, name: &str) -> String {
+        name.to_string()
+    }
+}
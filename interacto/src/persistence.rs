@@ -0,0 +1,671 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::{any::Any, fmt};
+
+use serde::{
+    de::{self, DeserializeOwned, DeserializeSeed, IgnoredAny, MapAccess, SeqAccess, Visitor},
+    ser::{self, SerializeSeq, SerializeStruct},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use crate::{historygroup::UndoableGroup, macrocmd::MacroCmd, undoble::Undoable, undohistory::UndoHistory};
+
+type SerializeFn = fn(&dyn Any) -> Option<&dyn erased_serde::Serialize>;
+
+type DeserializeFn =
+    fn(&mut dyn erased_serde::Deserializer<'_>) -> Result<Box<dyn Undoable>, erased_serde::Error>;
+
+/**
+ * The names of the built-in composite types, saved with their undoable objects.
+ */
+const GROUP_TYPE: &str = "interacto::group";
+const MACRO_TYPE: &str = "interacto::macro";
+
+/**
+ * A type of undoable object that can be saved and loaded.
+ */
+struct UndoableType {
+    name: &'static str,
+    serialize: SerializeFn,
+    deserialize: DeserializeFn,
+}
+
+fn serialize_as<T: Serialize + 'static>(
+    undoable: &dyn Any,
+) -> Option<&dyn erased_serde::Serialize> {
+    undoable
+        .downcast_ref::<T>()
+        .map(|undoable| undoable as &dyn erased_serde::Serialize)
+}
+
+fn deserialize_as<T: Undoable + DeserializeOwned + 'static>(
+    deserializer: &mut dyn erased_serde::Deserializer<'_>,
+) -> Result<Box<dyn Undoable>, erased_serde::Error> {
+    erased_serde::deserialize::<T>(deserializer)
+        .map(|undoable| Box::new(undoable) as Box<dyn Undoable>)
+}
+
+/**
+ * The outcome of loading a history.
+ */
+#[derive(PartialEq, Eq, Clone, Debug, Default)]
+pub struct LoadReport {
+    /**
+     * The names of the saved types that are not registered, in their order of appearance.
+     */
+    pub unknown_types: Vec<String>,
+
+    /**
+     * The number of saved undoable objects that have not been restored: the ones of an unknown type,
+     * plus the ones that cannot be reached anymore without them (older undos, newer redos).
+     */
+    pub dropped: usize,
+}
+
+/**
+ * The types of undoable objects that can be saved and loaded, identified by a stable name.
+ * A registered type must be serializable and return itself from 'Undoable::as_any'.
+ * Macro commands ('MacroCmd') and groups ('UndoableGroup') are built in: they are saved with their
+ * undoable objects, which must be of registered types. The names starting with 'interacto::' are reserved.
+ * A restored macro command can be undone and redone, but not executed again.
+ * A history is saved as its max size, its undo stack, its redo stack and its memory budget, with any serde format.
+ * Only the histories that implement 'UndoHistory' can be saved: the branches of a 'TreeUndoHistory'
+ * do not fit in two stacks, so tree histories are not supported.
+ */
+#[derive(Default)]
+pub struct UndoableTypeRegistry {
+    types: Vec<UndoableType>,
+}
+
+impl UndoableTypeRegistry {
+    pub fn new() -> Self {
+        Self { types: Vec::new() }
+    }
+
+    /**
+     * Registers a type of undoable objects. Registering a name again replaces the previous type.
+     * @param name - The name that identifies the type in saved histories. It must not change between versions.
+     */
+    pub fn register<T: Undoable + Serialize + DeserializeOwned + 'static>(
+        &mut self,
+        name: &'static str,
+    ) -> &mut Self {
+        self.types
+            .retain(|undoable_type| undoable_type.name != name);
+        self.types.push(UndoableType {
+            name,
+            serialize: serialize_as::<T>,
+            deserialize: deserialize_as::<T>,
+        });
+        self
+    }
+
+    /**
+     * @returns True if a type is registered with the given name.
+     */
+    pub fn is_registered(&self, name: &str) -> bool {
+        self.find(name).is_some()
+    }
+
+    fn find(&self, name: &str) -> Option<&UndoableType> {
+        self.types
+            .iter()
+            .find(|undoable_type| undoable_type.name == name)
+    }
+
+    fn entry<'a>(&'a self, undoable: &'a dyn Undoable) -> Option<SavedEntry<'a>> {
+        let any = undoable.as_any()?;
+        if let Some(group) = any.downcast_ref::<UndoableGroup>() {
+            return Some(SavedEntry {
+                name: GROUP_TYPE,
+                data: SavedData::Composite(SavedComposite {
+                    name: Some(group.get_undo_name()),
+                    undoables: SavedStack {
+                        registry: self,
+                        undoables: group.get_undoables().iter().map(|undoable| undoable.as_ref()).collect(),
+                    },
+                }),
+            });
+        }
+        if let Some(macro_cmd) = any.downcast_ref::<MacroCmd>() {
+            return Some(SavedEntry {
+                name: MACRO_TYPE,
+                data: SavedData::Composite(SavedComposite {
+                    name: macro_cmd.get_name().map(str::to_string),
+                    undoables: SavedStack {
+                        registry: self,
+                        undoables: macro_cmd.get_cmds().iter().map(|cmd| cmd.as_ref() as &dyn Undoable).collect(),
+                    },
+                }),
+            });
+        }
+        self.types.iter().find_map(|undoable_type| {
+            (undoable_type.serialize)(any).map(|data| SavedEntry {
+                name: undoable_type.name,
+                data: SavedData::Registered(data),
+            })
+        })
+    }
+
+    /**
+     * Saves a history.
+     * @returns An error if the history contains an undoable object whose type is not registered,
+     * including in a macro command or a group.
+     */
+    pub fn save<H: UndoHistory + ?Sized, S: Serializer>(
+        &self,
+        history: &H,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        SavedHistory {
+            registry: self,
+            history,
+        }
+        .serialize(serializer)
+    }
+
+    /**
+     * Replaces the content of a history with a saved one.
+     * Undoable objects of an unknown type cannot be restored: they are dropped, with the ones that depend on them,
+     * so that the restored history stays consistent. A macro command or a group that contains an undoable object
+     * of an unknown type is dropped as a whole.
+     * The memory budget of the history is kept if the saved history has none (saved by an older version).
+     * @returns What has not been restored.
+     */
+    pub fn load<'de, H: UndoHistory + ?Sized, D: Deserializer<'de>>(
+        &self,
+        history: &mut H,
+        deserializer: D,
+    ) -> Result<LoadReport, D::Error> {
+        let saved = HistorySeed { registry: self }.deserialize(deserializer)?;
+        let mut report = LoadReport::default();
+        let undos = keep_reachable(saved.undos, &mut report);
+        let redos = keep_reachable(saved.redos, &mut report);
+        history.set_size_max(saved.size_max);
+        if let Some(memory_max) = saved.memory_max {
+            history.set_memory_max(memory_max);
+        }
+        history.restore(undos, redos);
+        Ok(report)
+    }
+
+    /**
+     * Saves a history as JSON.
+     */
+    pub fn to_json<H: UndoHistory + ?Sized>(
+        &self,
+        history: &H,
+    ) -> Result<String, serde_json::Error> {
+        let mut json = Vec::new();
+        self.save(history, &mut serde_json::Serializer::new(&mut json))?;
+        Ok(String::from_utf8(json).expect("JSON is valid UTF-8"))
+    }
+
+    /**
+     * Replaces the content of a history with one saved as JSON.
+     */
+    pub fn from_json<H: UndoHistory + ?Sized>(
+        &self,
+        history: &mut H,
+        json: &str,
+    ) -> Result<LoadReport, serde_json::Error> {
+        let mut deserializer = serde_json::Deserializer::from_str(json);
+        let report = self.load(history, &mut deserializer)?;
+        deserializer.end()?;
+        Ok(report)
+    }
+
+    /**
+     * Saves a history in a compact binary format (MessagePack).
+     */
+    pub fn to_binary<H: UndoHistory + ?Sized>(
+        &self,
+        history: &H,
+    ) -> Result<Vec<u8>, rmp_serde::encode::Error> {
+        let mut bytes = Vec::new();
+        self.save(history, &mut rmp_serde::Serializer::new(&mut bytes))?;
+        Ok(bytes)
+    }
+
+    /**
+     * Replaces the content of a history with one saved in the binary format (see 'to_binary').
+     */
+    pub fn from_binary<H: UndoHistory + ?Sized>(
+        &self,
+        history: &mut H,
+        bytes: &[u8],
+    ) -> Result<LoadReport, rmp_serde::decode::Error> {
+        self.load(history, &mut rmp_serde::Deserializer::new(bytes))
+    }
+}
+
+/**
+ * Keeps the saved entries that follow the last one of an unknown type.
+ */
+fn keep_reachable(entries: Vec<LoadedEntry>, report: &mut LoadReport) -> Vec<Box<dyn Undoable>> {
+    let mut kept = Vec::new();
+    for entry in entries {
+        match entry {
+            LoadedEntry::Known(undoable) => kept.push(undoable),
+            LoadedEntry::Unknown(name) => {
+                report.dropped += kept.len() + 1;
+                report.unknown_types.push(name);
+                kept.clear();
+            }
+        }
+    }
+    kept
+}
+
+struct SavedHistory<'a, H: ?Sized> {
+    registry: &'a UndoableTypeRegistry,
+    history: &'a H,
+}
+
+impl<H: UndoHistory + ?Sized> Serialize for SavedHistory<'_, H> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("History", 4)?;
+        state.serialize_field("size_max", &self.history.get_size_max())?;
+        state.serialize_field("undos", &SavedStack::new(self.registry, self.history.get_undo()))?;
+        state.serialize_field("redos", &SavedStack::new(self.registry, self.history.get_redo()))?;
+        state.serialize_field("memory_max", &self.history.get_memory_max())?;
+        state.end()
+    }
+}
+
+struct SavedStack<'a> {
+    registry: &'a UndoableTypeRegistry,
+    undoables: Vec<&'a dyn Undoable>,
+}
+
+impl<'a> SavedStack<'a> {
+    fn new(registry: &'a UndoableTypeRegistry, undoables: &'a [Box<dyn Undoable>]) -> Self {
+        Self {
+            registry,
+            undoables: undoables.iter().map(|undoable| undoable.as_ref()).collect(),
+        }
+    }
+}
+
+impl Serialize for SavedStack<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.undoables.len()))?;
+        for undoable in &self.undoables {
+            let entry = self.registry.entry(*undoable).ok_or_else(|| {
+                ser::Error::custom(format!(
+                    "cannot save the undoable '{}': its type is not registered",
+                    undoable.get_undo_name()
+                ))
+            })?;
+            seq.serialize_element(&entry)?;
+        }
+        seq.end()
+    }
+}
+
+struct SavedEntry<'a> {
+    name: &'static str,
+    data: SavedData<'a>,
+}
+
+enum SavedData<'a> {
+    Registered(&'a dyn erased_serde::Serialize),
+    Composite(SavedComposite<'a>),
+}
+
+/**
+ * The data of a macro command or a group: its undo name and its undoable objects.
+ */
+struct SavedComposite<'a> {
+    name: Option<String>,
+    undoables: SavedStack<'a>,
+}
+
+impl Serialize for SavedEntry<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("Entry", 2)?;
+        state.serialize_field("type", self.name)?;
+        match &self.data {
+            SavedData::Registered(data) => state.serialize_field("data", data)?,
+            SavedData::Composite(composite) => state.serialize_field("data", composite)?,
+        }
+        state.end()
+    }
+}
+
+impl Serialize for SavedComposite<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("Composite", 2)?;
+        state.serialize_field("name", &self.name)?;
+        state.serialize_field("undoables", &self.undoables)?;
+        state.end()
+    }
+}
+
+struct LoadedHistory {
+    size_max: u64,
+    undos: Vec<LoadedEntry>,
+    redos: Vec<LoadedEntry>,
+    /**
+     * None if the saved history has no memory budget field.
+     */
+    memory_max: Option<Option<usize>>,
+}
+
+enum LoadedEntry {
+    Known(Box<dyn Undoable>),
+    Unknown(String),
+}
+
+#[derive(Deserialize)]
+#[serde(field_identifier, rename_all = "snake_case")]
+enum HistoryField {
+    SizeMax,
+    Undos,
+    Redos,
+    MemoryMax,
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Deserialize)]
+#[serde(field_identifier, rename_all = "snake_case")]
+enum EntryField {
+    Type,
+    Data,
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Clone, Copy)]
+struct HistorySeed<'a> {
+    registry: &'a UndoableTypeRegistry,
+}
+
+impl<'de> DeserializeSeed<'de> for HistorySeed<'_> {
+    type Value = LoadedHistory;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_struct("History", &["size_max", "undos", "redos", "memory_max"], self)
+    }
+}
+
+impl<'de> Visitor<'de> for HistorySeed<'_> {
+    type Value = LoadedHistory;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a saved undo history")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let stack = StackSeed {
+            registry: self.registry,
+        };
+        let size_max = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let undos = seq
+            .next_element_seed(stack)?
+            .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        let redos = seq
+            .next_element_seed(stack)?
+            .ok_or_else(|| de::Error::invalid_length(2, &self))?;
+        let memory_max = seq.next_element()?;
+        Ok(LoadedHistory {
+            size_max,
+            undos,
+            redos,
+            memory_max,
+        })
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let stack = StackSeed {
+            registry: self.registry,
+        };
+        let mut size_max = None;
+        let mut undos = None;
+        let mut redos = None;
+        let mut memory_max = None;
+        while let Some(field) = map.next_key()? {
+            match field {
+                HistoryField::SizeMax => size_max = Some(map.next_value()?),
+                HistoryField::Undos => undos = Some(map.next_value_seed(stack)?),
+                HistoryField::Redos => redos = Some(map.next_value_seed(stack)?),
+                HistoryField::MemoryMax => memory_max = Some(map.next_value()?),
+                HistoryField::Other => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        Ok(LoadedHistory {
+            size_max: size_max.ok_or_else(|| de::Error::missing_field("size_max"))?,
+            undos: undos.ok_or_else(|| de::Error::missing_field("undos"))?,
+            redos: redos.ok_or_else(|| de::Error::missing_field("redos"))?,
+            memory_max,
+        })
+    }
+}
+
+#[derive(Clone, Copy)]
+struct StackSeed<'a> {
+    registry: &'a UndoableTypeRegistry,
+}
+
+impl<'de> DeserializeSeed<'de> for StackSeed<'_> {
+    type Value = Vec<LoadedEntry>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de> Visitor<'de> for StackSeed<'_> {
+    type Value = Vec<LoadedEntry>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a sequence of saved undoable objects")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut entries = Vec::new();
+        while let Some(entry) = seq.next_element_seed(EntrySeed {
+            registry: self.registry,
+        })? {
+            entries.push(entry);
+        }
+        Ok(entries)
+    }
+}
+
+#[derive(Clone, Copy)]
+struct EntrySeed<'a> {
+    registry: &'a UndoableTypeRegistry,
+}
+
+impl<'de> DeserializeSeed<'de> for EntrySeed<'_> {
+    type Value = LoadedEntry;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_struct("Entry", &["type", "data"], self)
+    }
+}
+
+impl<'de> Visitor<'de> for EntrySeed<'_> {
+    type Value = LoadedEntry;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a saved undoable object")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let name: String = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        seq.next_element_seed(DataSeed {
+            registry: self.registry,
+            name,
+        })?
+        .ok_or_else(|| de::Error::invalid_length(1, &self))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut name: Option<String> = None;
+        let mut entry = None;
+        while let Some(field) = map.next_key()? {
+            match field {
+                EntryField::Type => name = Some(map.next_value()?),
+                EntryField::Data => {
+                    let name = name.take().ok_or_else(|| {
+                        de::Error::custom(
+                            "the type of a saved undoable object must precede its data",
+                        )
+                    })?;
+                    entry = Some(map.next_value_seed(DataSeed {
+                        registry: self.registry,
+                        name,
+                    })?);
+                }
+                EntryField::Other => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        entry.ok_or_else(|| de::Error::missing_field("data"))
+    }
+}
+
+/**
+ * Loads the data of a saved undoable object, given the name of its type.
+ */
+struct DataSeed<'a> {
+    registry: &'a UndoableTypeRegistry,
+    name: String,
+}
+
+impl<'de> DeserializeSeed<'de> for DataSeed<'_> {
+    type Value = LoadedEntry;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        if self.name == GROUP_TYPE || self.name == MACRO_TYPE {
+            let composite = CompositeSeed {
+                registry: self.registry,
+            }
+            .deserialize(deserializer)?;
+            return Ok(composite.into_entry(&self.name));
+        }
+        match self.registry.find(&self.name) {
+            Some(undoable_type) => {
+                let mut deserializer = <dyn erased_serde::Deserializer>::erase(deserializer);
+                (undoable_type.deserialize)(&mut deserializer)
+                    .map(LoadedEntry::Known)
+                    .map_err(de::Error::custom)
+            }
+            None => {
+                IgnoredAny::deserialize(deserializer)?;
+                Ok(LoadedEntry::Unknown(self.name))
+            }
+        }
+    }
+}
+
+struct LoadedComposite {
+    name: Option<String>,
+    undoables: Vec<LoadedEntry>,
+}
+
+impl LoadedComposite {
+    /**
+     * @returns The restored macro command or group, or the first unknown type of its undoable objects.
+     */
+    fn into_entry(self, type_name: &str) -> LoadedEntry {
+        let mut undoables = Vec::with_capacity(self.undoables.len());
+        for entry in self.undoables {
+            match entry {
+                LoadedEntry::Known(undoable) => undoables.push(undoable),
+                unknown @ LoadedEntry::Unknown(_) => return unknown,
+            }
+        }
+        if type_name == GROUP_TYPE {
+            LoadedEntry::Known(Box::new(UndoableGroup::restore(self.name.unwrap_or_default(), undoables)))
+        } else {
+            LoadedEntry::Known(Box::new(MacroCmd::restore(self.name, undoables)))
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(field_identifier, rename_all = "snake_case")]
+enum CompositeField {
+    Name,
+    Undoables,
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Clone, Copy)]
+struct CompositeSeed<'a> {
+    registry: &'a UndoableTypeRegistry,
+}
+
+impl<'de> DeserializeSeed<'de> for CompositeSeed<'_> {
+    type Value = LoadedComposite;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_struct("Composite", &["name", "undoables"], self)
+    }
+}
+
+impl<'de> Visitor<'de> for CompositeSeed<'_> {
+    type Value = LoadedComposite;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a saved macro command or group")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let name = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let undoables = seq
+            .next_element_seed(StackSeed {
+                registry: self.registry,
+            })?
+            .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        Ok(LoadedComposite { name, undoables })
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut name = None;
+        let mut undoables = None;
+        while let Some(field) = map.next_key()? {
+            match field {
+                CompositeField::Name => name = Some(map.next_value()?),
+                CompositeField::Undoables => {
+                    undoables = Some(map.next_value_seed(StackSeed {
+                        registry: self.registry,
+                    })?)
+                }
+                CompositeField::Other => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        Ok(LoadedComposite {
+            name: name.ok_or_else(|| de::Error::missing_field("name"))?,
+            undoables: undoables.ok_or_else(|| de::Error::missing_field("undoables"))?,
+        })
+    }
+}
@@ -0,0 +1,39 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use crate::{
+    event::UIEvent,
+    fsm::Fsm,
+    interaction::{fsm_interaction, is_pointer_down, point_data, PointData},
+};
+
+/**
+ * The press of a pointer button. The interaction stops as soon as the button is pressed.
+ */
+pub struct Press {
+    fsm: Fsm<UIEvent, PointData>,
+}
+
+impl Press {
+    pub fn new() -> Self {
+        let mut fsm: Fsm<UIEvent, PointData> = Fsm::new();
+        let init = fsm.get_init_state();
+        let pressed = fsm.add_terminal_state("pressed");
+        fsm.add_transition(init, pressed, is_pointer_down)
+            .with_action(|event, data| *data = point_data(event).unwrap_or_default());
+        Self { fsm }
+    }
+}
+
+fsm_interaction!(Press, PointData, Default);
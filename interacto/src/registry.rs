@@ -0,0 +1,236 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::any::Any;
+
+use crate::{
+    command::{CmdStatus, Command},
+    subject::{Subject, Subscription},
+};
+
+/**
+ * A command stored in a registry, whatever the type of its child command.
+ */
+pub trait AnyCommand {
+    fn get_status(&self) -> CmdStatus;
+
+    /**
+     * Flushes the command.
+     * The command must not be used after that.
+     */
+    fn flush(&mut self);
+
+    fn cancel(&mut self);
+
+    /**
+     * @returns The command, to downcast it to its concrete 'Command' type.
+     */
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<T: 'static> AnyCommand for Command<T> {
+    fn get_status(&self) -> CmdStatus {
+        Command::get_status(self)
+    }
+
+    fn flush(&mut self) {
+        Command::flush(self)
+    }
+
+    fn cancel(&mut self) {
+        Command::cancel(self)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/**
+ * The identifier of a command in a registry.
+ */
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub struct CmdId(u64);
+
+type CmdObserver = dyn FnMut(CmdId, &dyn AnyCommand);
+
+/**
+ * A register of the executed commands.
+ * When the registry is full, its oldest commands are flushed.
+ */
+pub struct CommandsRegistry {
+    /**
+     * The saved commands, from the oldest to the newest.
+     */
+    cmds: Vec<(CmdId, Box<dyn AnyCommand>)>,
+
+    /**
+     * The max number of saved commands.
+     */
+    size_max: usize,
+
+    next_id: u64,
+
+    added: Subject<CmdObserver>,
+
+    removed: Subject<CmdObserver>,
+}
+
+impl CommandsRegistry {
+    pub fn new() -> Self {
+        Self {
+            cmds: Vec::new(),
+            size_max: 50,
+            next_id: 0,
+            added: Subject::new(),
+            removed: Subject::new(),
+        }
+    }
+
+    /**
+     * Adds a done command to the registry.
+     * If the registry is full, its oldest command is removed and flushed.
+     * Commands that are not done are not added. If the max size of the registry is 0,
+     * the command is not added either and is directly flushed.
+     * @param cmd - The command to add.
+     * @returns The identifier of the command in the registry, or None if the command was not added.
+     */
+    pub fn add<T: 'static>(&mut self, mut cmd: Command<T>) -> Option<CmdId> {
+        if !cmd.is_done() {
+            return None;
+        }
+        if self.size_max == 0 {
+            cmd.flush();
+            return None;
+        }
+        if self.cmds.len() == self.size_max {
+            self.remove_at(0);
+        }
+        let id = CmdId(self.next_id);
+        self.next_id += 1;
+        self.cmds.push((id, Box::new(cmd)));
+        if let Some((_, cmd)) = self.cmds.last() {
+            self.added.notify(|observer| observer(id, cmd.as_ref()));
+        }
+        Some(id)
+    }
+
+    /**
+     * Removes and flushes the given command.
+     * @returns False if the registry does not contain the command.
+     */
+    pub fn remove(&mut self, id: CmdId) -> bool {
+        match self.position(id) {
+            Some(index) => {
+                self.remove_at(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /**
+     * Cancels the given command and removes it from the registry.
+     * @returns False if the registry does not contain the command.
+     */
+    pub fn cancel(&mut self, id: CmdId) -> bool {
+        match self.position(id) {
+            Some(index) => {
+                self.cmds[index].1.cancel();
+                self.remove_at(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /**
+     * Removes and flushes all the commands of the registry.
+     */
+    pub fn clear(&mut self) {
+        while !self.cmds.is_empty() {
+            self.remove_at(0);
+        }
+    }
+
+    pub fn get(&self, id: CmdId) -> Option<&dyn AnyCommand> {
+        self.position(id).map(|index| self.cmds[index].1.as_ref())
+    }
+
+    /**
+     * @returns The saved commands, from the oldest to the newest.
+     */
+    pub fn get_commands(&self) -> impl Iterator<Item = (CmdId, &dyn AnyCommand)> {
+        self.cmds.iter().map(|(id, cmd)| (*id, cmd.as_ref()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.cmds.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cmds.is_empty()
+    }
+
+    /**
+     * @returns The max number of saved commands.
+     */
+    pub fn get_size_max(&self) -> usize {
+        self.size_max
+    }
+
+    /**
+     * Changes the max number of saved commands.
+     * The oldest commands are removed and flushed if the registry contains too many commands.
+     * @param max - The max number of saved commands.
+     */
+    pub fn set_size_max(&mut self, max: usize) {
+        while self.cmds.len() > max {
+            self.remove_at(0);
+        }
+        self.size_max = max;
+    }
+
+    /**
+     * Observes the commands added to the registry.
+     * @returns The subscription of the observer: the observer is removed when it is dropped.
+     */
+    pub fn subscribe_added(&self, observer: impl FnMut(CmdId, &dyn AnyCommand) + 'static) -> Subscription {
+        self.added.subscribe(Box::new(observer))
+    }
+
+    /**
+     * Observes the commands removed from the registry. Removed commands are notified once flushed.
+     * @returns The subscription of the observer: the observer is removed when it is dropped.
+     */
+    pub fn subscribe_removed(&self, observer: impl FnMut(CmdId, &dyn AnyCommand) + 'static) -> Subscription {
+        self.removed.subscribe(Box::new(observer))
+    }
+
+    fn position(&self, id: CmdId) -> Option<usize> {
+        self.cmds.iter().position(|(cmd_id, _)| *cmd_id == id)
+    }
+
+    fn remove_at(&mut self, index: usize) {
+        let (id, mut cmd) = self.cmds.remove(index);
+        cmd.flush();
+        self.removed.notify(|observer| observer(id, cmd.as_ref()));
+    }
+}
+
+impl Default for CommandsRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,124 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::{
+    cell::{Cell, RefCell},
+    rc::{Rc, Weak},
+};
+
+type Observers<F> = Rc<RefCell<Vec<(u64, Rc<RefCell<Box<F>>>)>>>;
+
+/**
+ * A stream of values that observers can subscribe to.
+ * The type parameter is the type of the observers, for example 'dyn FnMut(&str)'.
+ */
+pub struct Subject<F: ?Sized> {
+    observers: Observers<F>,
+    next_id: Cell<u64>,
+}
+
+impl<F: ?Sized + 'static> Subject<F> {
+    pub fn new() -> Self {
+        Self {
+            observers: Rc::new(RefCell::new(Vec::new())),
+            next_id: Cell::new(0),
+        }
+    }
+
+    /**
+     * Adds an observer to the stream.
+     * @returns The subscription of the observer: the observer is removed when it is dropped.
+     */
+    pub fn subscribe(&self, observer: Box<F>) -> Subscription {
+        let id = self.next_id.get();
+        self.next_id.set(id + 1);
+        self.observers
+            .borrow_mut()
+            .push((id, Rc::new(RefCell::new(observer))));
+
+        let observers: Weak<_> = Rc::downgrade(&self.observers);
+        Subscription {
+            unsubscribe: Some(Box::new(move || {
+                if let Some(observers) = observers.upgrade() {
+                    observers.borrow_mut().retain(|(obs_id, _)| *obs_id != id);
+                }
+            })),
+        }
+    }
+
+    /**
+     * Notifies all the observers, in their order of subscription.
+     * Observers can subscribe or unsubscribe while being notified: the changes apply to the next notification.
+     * @param call - Calls the given observer with the published value.
+     */
+    pub fn notify(&self, mut call: impl FnMut(&mut F)) {
+        let observers: Vec<Rc<RefCell<Box<F>>>> = self
+            .observers
+            .borrow()
+            .iter()
+            .map(|(_, observer)| observer.clone())
+            .collect();
+        for observer in observers {
+            if let Ok(mut observer) = observer.try_borrow_mut() {
+                call(&mut **observer);
+            }
+        }
+    }
+
+    pub fn observer_count(&self) -> usize {
+        self.observers.borrow().len()
+    }
+}
+
+impl<F: ?Sized + 'static> Default for Subject<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/**
+ * The subscription of an observer to a subject.
+ * Dropping the subscription unsubscribes the observer.
+ */
+pub struct Subscription {
+    unsubscribe: Option<Box<dyn FnOnce()>>,
+}
+
+impl Subscription {
+    /**
+     * @returns A subscription that is bound to no subject: dropping it does nothing.
+     */
+    pub fn empty() -> Self {
+        Self { unsubscribe: None }
+    }
+
+    /**
+     * Removes the observer from its subject.
+     */
+    pub fn unsubscribe(mut self) {
+        self.run_unsubscribe();
+    }
+
+    fn run_unsubscribe(&mut self) {
+        if let Some(unsubscribe) = self.unsubscribe.take() {
+            unsubscribe();
+        }
+    }
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        self.run_unsubscribe();
+    }
+}
@@ -16,15 +16,17 @@ use std::sync::{Arc, Mutex};
 
 use crate::{
     anoncmd::AnonCmd,
-    command::{Command, CustomCmd},
+    command::{Command, CommandBase},
 };
 
 #[test]
+#[allow(clippy::bool_assert_comparison)]
 fn can_do_ok_cmd() {
     assert_eq!(AnonCmd::new(|| {}).as_command().can_execute(), true);
 }
 
 #[test]
+#[allow(clippy::bool_assert_comparison)]
 fn execute() {
     let ok = Arc::new(Mutex::new(false));
     let mut cmd = Command::new(AnonCmd::new(|| {
@@ -36,6 +38,7 @@ fn execute() {
 }
 
 #[test]
+#[allow(clippy::bool_assert_comparison)]
 fn had_effect() {
     let mut cmd = Command::new(AnonCmd::new(|| {}));
     cmd.execute();
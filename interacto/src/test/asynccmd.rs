@@ -0,0 +1,243 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::{
+    future::Future,
+    pin::{pin, Pin},
+    task::{Context, Poll, Waker},
+};
+
+use std::{cell::Cell, convert::Infallible, rc::Rc};
+
+use crate::{
+    asynccmd::AsyncCustomCmd,
+    command::{CmdOutcome, CmdStatus, Command, CommandBase, CustomCmd},
+    historyhandle::HistoryHandle,
+    linearhistory::LinearHistoryImpl,
+    undoble::Undoable,
+};
+
+/// A minimal single-threaded executor that polls the future until it completes.
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
+
+/// A future that is pending the first time it is polled.
+struct YieldNow(bool);
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            Poll::Ready(())
+        } else {
+            self.0 = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
+struct StubAsyncCmd {
+    can_do_value: bool,
+    fail: bool,
+    crash: bool,
+    exec: u32,
+    mementos: u32,
+}
+
+impl StubAsyncCmd {
+    fn new() -> Self {
+        Self {
+            can_do_value: true,
+            fail: false,
+            crash: false,
+            exec: 0,
+            mementos: 0,
+        }
+    }
+}
+
+impl CommandBase for StubAsyncCmd {
+    fn create_memento(&mut self) {
+        self.mementos += 1;
+    }
+
+    fn can_execute(&self) -> bool {
+        self.can_do_value
+    }
+}
+
+impl AsyncCustomCmd for StubAsyncCmd {
+    type Error = String;
+
+    async fn execution(&mut self) -> Result<(), Self::Error> {
+        YieldNow(false).await;
+        self.exec += 1;
+        if self.crash {
+            panic!("async crash");
+        }
+        if self.fail {
+            Err("Cmd err".to_string())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[test]
+fn execute_async() {
+    let mut cmd = StubAsyncCmd::new().as_command();
+    assert_eq!(block_on(cmd.execute_async()), CmdOutcome::Executed);
+    assert_eq!(cmd.get_status(), CmdStatus::Executed);
+    assert_eq!(cmd.child.exec, 1);
+}
+
+#[test]
+fn status_unchanged_while_pending() {
+    let mut cmd = StubAsyncCmd::new().as_command();
+    {
+        let mut future = pin!(cmd.execute_async());
+        let mut cx = Context::from_waker(Waker::noop());
+        assert!(future.as_mut().poll(&mut cx).is_pending());
+    }
+    assert_eq!(cmd.get_status(), CmdStatus::Created);
+}
+
+#[test]
+fn execute_async_two_times() {
+    let mut cmd = StubAsyncCmd::new().as_command();
+    block_on(cmd.execute_async());
+    block_on(cmd.execute_async());
+    assert_eq!(cmd.child.exec, 2);
+    assert_eq!(cmd.child.mementos, 1);
+}
+
+#[test]
+fn execute_async_failure() {
+    let mut cmd = StubAsyncCmd::new().as_command();
+    cmd.child.fail = true;
+    assert_eq!(
+        block_on(cmd.execute_async()),
+        CmdOutcome::Failed("Cmd err".to_string())
+    );
+    assert_eq!(cmd.get_status(), CmdStatus::Executed);
+}
+
+#[test]
+fn cannot_execute_async_when_cannot_do() {
+    let mut cmd = StubAsyncCmd::new().as_command();
+    cmd.child.can_do_value = false;
+    assert_eq!(block_on(cmd.execute_async()), CmdOutcome::NotExecutable);
+    assert_eq!(cmd.get_status(), CmdStatus::Created);
+    assert_eq!(cmd.child.mementos, 0);
+}
+
+#[test]
+fn cannot_execute_async_when_done() {
+    let mut cmd = StubAsyncCmd::new().as_command();
+    cmd.done();
+    assert_eq!(block_on(cmd.execute_async()), CmdOutcome::NotExecutable);
+    assert_eq!(cmd.child.exec, 0);
+}
+
+#[test]
+#[should_panic(expected = "async crash")]
+fn execute_async_panic_not_caught_by_default() {
+    let mut cmd = StubAsyncCmd::new().as_command();
+    cmd.child.crash = true;
+    block_on(cmd.execute_async());
+}
+
+#[test]
+fn execute_async_catches_panics() {
+    let mut cmd = StubAsyncCmd::new().as_command();
+    cmd.child.crash = true;
+    cmd.set_catch_panics(true);
+    assert_eq!(
+        block_on(cmd.execute_async()),
+        CmdOutcome::Panicked("async crash".to_string())
+    );
+    assert_eq!(cmd.get_status(), CmdStatus::Executed);
+}
+
+/// An undoable command that can be executed synchronously and asynchronously.
+struct Incr {
+    value: Rc<Cell<i32>>,
+}
+
+impl CommandBase for Incr {}
+
+impl CustomCmd for Incr {
+    type Error = Infallible;
+
+    fn execution(&mut self) -> Result<(), Self::Error> {
+        self.value.set(self.value.get() + 1);
+        Ok(())
+    }
+}
+
+impl AsyncCustomCmd for Incr {
+    type Error = Infallible;
+
+    async fn execution(&mut self) -> Result<(), Self::Error> {
+        YieldNow(false).await;
+        self.value.set(self.value.get() + 1);
+        Ok(())
+    }
+}
+
+impl Undoable for Incr {
+    fn undo(&mut self) {
+        self.value.set(self.value.get() - 1);
+    }
+
+    fn redo(&mut self) {
+        self.value.set(self.value.get() + 1);
+    }
+
+    fn get_undo_name(&self) -> String {
+        "incr".to_string()
+    }
+}
+
+#[test]
+fn sync_and_async_command() {
+    let value = Rc::new(Cell::new(0));
+    let mut cmd = Incr { value: value.clone() }.as_command();
+    assert!(cmd.can_execute());
+    assert!(cmd.execute().is_executed());
+    assert!(block_on(cmd.execute_async()).is_executed());
+    assert_eq!(value.get(), 2);
+}
+
+#[test]
+fn async_command_done_added_to_history() {
+    let value = Rc::new(Cell::new(0));
+    let history = HistoryHandle::new(LinearHistoryImpl::new());
+    let mut cmd = Command::new(Incr { value: value.clone() });
+    assert!(block_on(cmd.execute_async()).is_executed());
+    cmd.set_history(history.clone());
+    cmd.done();
+    assert!(cmd.child.is_in_history());
+    history.undo();
+    assert_eq!(value.get(), 0);
+}
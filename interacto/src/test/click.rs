@@ -0,0 +1,67 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use crate::{
+    click::Click,
+    event::{MouseButton, PointerEvent, UIEvent},
+    interaction::Interaction,
+};
+
+use super::interaction::{cancelled, down, drag, record, stopped, up};
+
+#[test]
+fn click() {
+    let mut click = Click::new();
+    let (steps, _sub) = record(&click);
+    click.process_event(&down(1.0, 2.0));
+    assert!(click.is_running());
+    assert!(stopped(&steps).is_none());
+    click.process_event(&up(1.0, 2.0));
+    let data = stopped(&steps).unwrap();
+    assert_eq!((data.x, data.y, data.button), (1.0, 2.0, MouseButton::Primary));
+    assert!(!click.is_running());
+}
+
+#[test]
+fn move_cancels_click() {
+    let mut click = Click::new();
+    let (steps, _sub) = record(&click);
+    click.process_event(&down(1.0, 2.0));
+    click.process_event(&drag(5.0, 2.0));
+    assert!(cancelled(&steps));
+    click.process_event(&up(5.0, 2.0));
+    assert!(stopped(&steps).is_none());
+}
+
+#[test]
+fn small_move_does_not_cancel_click() {
+    let mut click = Click::new();
+    let (steps, _sub) = record(&click);
+    click.process_event(&down(1.0, 2.0));
+    click.process_event(&drag(2.0, 2.0));
+    assert!(!cancelled(&steps));
+    click.process_event(&up(2.0, 2.0));
+    assert_eq!(stopped(&steps).map(|data| (data.x, data.y)), Some((2.0, 2.0)));
+}
+
+#[test]
+fn release_of_other_button_ignored() {
+    let mut click = Click::new();
+    let (steps, _sub) = record(&click);
+    click.process_event(&down(1.0, 2.0));
+    assert!(!click.process_event(&UIEvent::PointerUp(PointerEvent::new(1.0, 2.0, MouseButton::Secondary))));
+    assert!(click.is_running());
+    click.process_event(&up(1.0, 2.0));
+    assert!(stopped(&steps).is_some());
+}
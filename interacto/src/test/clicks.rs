@@ -0,0 +1,140 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::time::Duration;
+
+use crate::{
+    clicks::Clicks,
+    clock::VirtualClock,
+    event::{MouseButton, PointerEvent, UIEvent},
+    interaction::Interaction,
+};
+
+use super::interaction::{cancelled, down, drag, mv, record, stopped, up};
+
+fn clicks(count: usize, clock: &VirtualClock) -> Clicks {
+    let mut interaction = Clicks::with_delay(count, Duration::from_millis(500));
+    interaction.set_clock(Box::new(clock.clone()));
+    interaction
+}
+
+#[test]
+#[should_panic]
+fn zero_clicks() {
+    Clicks::new(0);
+}
+
+#[test]
+fn count() {
+    assert_eq!(Clicks::new(3).get_count(), 3);
+}
+
+#[test]
+fn single_click() {
+    let clock = VirtualClock::new();
+    let mut interaction = clicks(1, &clock);
+    let (steps, _sub) = record(&interaction);
+    interaction.process_event(&down(1.0, 1.0));
+    interaction.process_event(&up(1.0, 1.0));
+    assert_eq!(stopped(&steps).unwrap().points.len(), 1);
+}
+
+#[test]
+fn three_clicks() {
+    let clock = VirtualClock::new();
+    let mut interaction = clicks(3, &clock);
+    let (steps, _sub) = record(&interaction);
+    for x in [1.0, 2.0, 3.0] {
+        assert!(stopped(&steps).is_none());
+        interaction.process_event(&down(x, 0.0));
+        interaction.process_event(&up(x, 0.0));
+        clock.advance(Duration::from_millis(400));
+    }
+    let points = stopped(&steps).unwrap().points;
+    assert_eq!(points.iter().map(|point| point.x).collect::<Vec<_>>(), vec![1.0, 2.0, 3.0]);
+    assert!(interaction.get_data().points.is_empty());
+}
+
+#[test]
+fn too_slow_clicks() {
+    let clock = VirtualClock::new();
+    let mut interaction = clicks(2, &clock);
+    let (steps, _sub) = record(&interaction);
+    interaction.process_event(&down(1.0, 1.0));
+    interaction.process_event(&up(1.0, 1.0));
+    clock.advance(Duration::from_millis(600));
+    interaction.check_timeout();
+    assert!(cancelled(&steps));
+    assert!(interaction.get_data().points.is_empty());
+}
+
+#[test]
+fn move_cancels_clicks() {
+    let clock = VirtualClock::new();
+    let mut interaction = clicks(2, &clock);
+    let (steps, _sub) = record(&interaction);
+    interaction.process_event(&down(1.0, 1.0));
+    interaction.process_event(&drag(5.0, 1.0));
+    assert!(cancelled(&steps));
+}
+
+#[test]
+fn move_between_clicks_cancels_clicks() {
+    let clock = VirtualClock::new();
+    let mut interaction = clicks(2, &clock);
+    let (steps, _sub) = record(&interaction);
+    interaction.process_event(&down(1.0, 1.0));
+    interaction.process_event(&up(1.0, 1.0));
+    interaction.process_event(&mv(5.0, 1.0));
+    assert!(cancelled(&steps));
+}
+
+#[test]
+fn small_moves_do_not_cancel_clicks() {
+    let clock = VirtualClock::new();
+    let mut interaction = clicks(2, &clock);
+    let (steps, _sub) = record(&interaction);
+    interaction.process_event(&down(1.0, 1.0));
+    interaction.process_event(&drag(2.0, 1.0));
+    interaction.process_event(&up(2.0, 1.0));
+    interaction.process_event(&mv(2.0, 2.0));
+    interaction.process_event(&down(2.0, 2.0));
+    interaction.process_event(&up(2.0, 2.0));
+    assert!(!cancelled(&steps));
+    assert_eq!(stopped(&steps).unwrap().points.len(), 2);
+}
+
+#[test]
+fn other_button_release_ignored() {
+    let clock = VirtualClock::new();
+    let mut interaction = clicks(1, &clock);
+    let (steps, _sub) = record(&interaction);
+    interaction.process_event(&down(1.0, 1.0));
+    assert!(!interaction.process_event(&UIEvent::PointerUp(PointerEvent::new(1.0, 1.0, MouseButton::Secondary))));
+    assert!(stopped(&steps).is_none());
+    interaction.process_event(&up(1.0, 1.0));
+    assert_eq!(stopped(&steps).unwrap().press.button, MouseButton::Primary);
+}
+
+#[test]
+fn other_button_press_cancels_clicks() {
+    let clock = VirtualClock::new();
+    let mut interaction = clicks(2, &clock);
+    let (steps, _sub) = record(&interaction);
+    interaction.process_event(&down(1.0, 1.0));
+    interaction.process_event(&up(1.0, 1.0));
+    interaction.process_event(&UIEvent::PointerDown(PointerEvent::new(1.0, 1.0, MouseButton::Secondary)));
+    assert!(cancelled(&steps));
+    assert!(stopped(&steps).is_none());
+}
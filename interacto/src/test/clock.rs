@@ -0,0 +1,52 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::time::Duration;
+
+use crate::clock::{Clock, SystemClock, VirtualClock};
+
+#[test]
+fn virtual_clock_starts_at_zero() {
+    assert_eq!(VirtualClock::new().now(), Duration::ZERO);
+}
+
+#[test]
+fn virtual_clock_advance() {
+    let clock = VirtualClock::new();
+    clock.advance(Duration::from_millis(100));
+    clock.advance(Duration::from_millis(50));
+    assert_eq!(clock.now(), Duration::from_millis(150));
+}
+
+#[test]
+fn virtual_clock_set() {
+    let clock = VirtualClock::new();
+    clock.set(Duration::from_secs(2));
+    assert_eq!(clock.now(), Duration::from_secs(2));
+}
+
+#[test]
+fn virtual_clock_clones_share_time() {
+    let clock = VirtualClock::new();
+    let clone = clock.clone();
+    clone.advance(Duration::from_secs(1));
+    assert_eq!(clock.now(), Duration::from_secs(1));
+}
+
+#[test]
+fn system_clock_is_monotonic() {
+    let clock = SystemClock::new();
+    let first = clock.now();
+    assert!(clock.now() >= first);
+}
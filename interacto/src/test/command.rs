@@ -11,11 +11,20 @@
  * You should have received a copy of the GNU General Public License
  * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
  */
-use crate::command::{CmdStatus, Command, CustomCmd};
+use std::{cell::Cell, convert::Infallible, rc::Rc};
+
+use crate::{
+    command::{CmdOutcome, CmdStatus, Command, CommandBase, CustomCmd, IllegalTransition},
+    historyhandle::HistoryHandle,
+    linearhistory::LinearHistoryImpl,
+    undoble::Undoable,
+    undohistory::UndoHistoryBase,
+};
 
 pub struct StubCmd {
     can_do_value: bool,
     exec: u32,
+    #[allow(dead_code)]
     effects: bool,
 }
 
@@ -35,21 +44,33 @@ impl StubCmd {
     }
 }
 
-impl CustomCmd for StubCmd {
-    fn execution(&mut self) {
-        self.exec += 1;
-    }
-
+impl CommandBase for StubCmd {
     fn can_execute(&self) -> bool {
         self.can_do_value
     }
 }
 
+impl CustomCmd for StubCmd {
+    type Error = Infallible;
+
+    fn execution(&mut self) -> Result<(), Self::Error> {
+        self.exec += 1;
+        Ok(())
+    }
+}
+
 #[test]
+#[allow(clippy::bool_assert_comparison)]
 fn cando_default() {
     struct Cmd;
+    impl CommandBase for Cmd {}
+
     impl CustomCmd for Cmd {
-        fn execution(&mut self) {}
+        type Error = Infallible;
+
+        fn execution(&mut self) -> Result<(), Self::Error> {
+            Ok(())
+        }
     }
     impl Cmd {
         pub fn new() -> Self {
@@ -79,33 +100,36 @@ fn command_status_after_flush() {
 fn command_cannot_do_it_when_flushed() {
     let mut cmd = Command::new(StubCmd::default());
     cmd.flush();
-    assert_eq!(cmd.execute(), false);
+    assert_eq!(cmd.execute(), CmdOutcome::NotExecutable);
 }
 
 #[test]
 fn command_cannot_do_it_when_done() {
     let mut cmd = Command::new(StubCmd::default());
     cmd.done();
-    assert_eq!(cmd.execute(), false);
+    assert_eq!(cmd.execute(), CmdOutcome::NotExecutable);
 }
 
 #[test]
 fn command_cannot_do_it_when_cancelled() {
     let mut cmd = Command::new(StubCmd::default());
     cmd.cancel();
-    assert_eq!(cmd.execute(), false);
+    assert_eq!(cmd.execute(), CmdOutcome::NotExecutable);
 }
 
 #[test]
 fn command_cannot_do_it_when_cannot_do_and_created() {
     let mut cmd = Command::new(StubCmd::default());
     cmd.child.can_do_value = false;
-    assert_eq!(cmd.execute(), false);
+    assert_eq!(cmd.execute(), CmdOutcome::NotExecutable);
 }
 
 #[test]
 fn command_can_do_it_when_can_do() {
-    assert_eq!(StubCmd::default().as_command().execute(), true);
+    assert_eq!(
+        StubCmd::default().as_command().execute(),
+        CmdOutcome::Executed
+    );
 }
 
 #[test]
@@ -116,6 +140,7 @@ fn command_is_executed_when_do_it() {
 }
 
 #[test]
+#[allow(clippy::bool_assert_comparison)]
 fn command_had_effect_when_done() {
     let mut cmd = Command::new(StubCmd::default());
     cmd.done();
@@ -123,11 +148,13 @@ fn command_had_effect_when_done() {
 }
 
 #[test]
+#[allow(clippy::bool_assert_comparison)]
 fn command_had_effect_when_not_done_and_created() {
     assert_eq!(StubCmd::default().as_command().had_effect(), false);
 }
 
 #[test]
+#[allow(clippy::bool_assert_comparison)]
 fn command_had_effect_when_not_done_and_cancelled() {
     let mut cmd = Command::new(StubCmd::default());
     cmd.cancel();
@@ -135,6 +162,7 @@ fn command_had_effect_when_not_done_and_cancelled() {
 }
 
 #[test]
+#[allow(clippy::bool_assert_comparison)]
 fn command_had_effect_when_not_done_and_flushed() {
     let mut cmd = Command::new(StubCmd::default());
     cmd.flush();
@@ -142,6 +170,7 @@ fn command_had_effect_when_not_done_and_flushed() {
 }
 
 #[test]
+#[allow(clippy::bool_assert_comparison)]
 fn command_had_effect_when_not_done_and_executed() {
     let mut cmd = Command::new(StubCmd::default());
     cmd.child.can_do_value = true;
@@ -181,11 +210,13 @@ fn command_done_when_executed() {
 }
 
 #[test]
+#[allow(clippy::bool_assert_comparison)]
 fn is_done_when_created() {
     assert_eq!(StubCmd::default().as_command().is_done(), false);
 }
 
 #[test]
+#[allow(clippy::bool_assert_comparison)]
 fn is_done_when_cancelled() {
     let mut cmd = Command::new(StubCmd::default());
     cmd.cancel();
@@ -193,6 +224,7 @@ fn is_done_when_cancelled() {
 }
 
 #[test]
+#[allow(clippy::bool_assert_comparison)]
 fn is_done_when_flushed() {
     let mut cmd = Command::new(StubCmd::default());
     cmd.flush();
@@ -200,6 +232,7 @@ fn is_done_when_flushed() {
 }
 
 #[test]
+#[allow(clippy::bool_assert_comparison)]
 fn is_done_when_done() {
     let mut cmd = Command::new(StubCmd::default());
     cmd.done();
@@ -207,6 +240,7 @@ fn is_done_when_done() {
 }
 
 #[test]
+#[allow(clippy::bool_assert_comparison)]
 fn is_done_when_executed() {
     let mut cmd = Command::new(StubCmd::default());
     cmd.execute();
@@ -228,23 +262,329 @@ fn executed_two_times() {
     assert_eq!(cmd.child.exec, 2);
 }
 
-// #[test]
-//    fn crash_in_execution_command_executed() {
-//     struct Cmd;
-//     impl CustomCmd for Cmd {
-//         fn execution(&mut self) {
-//             panic!()
-//         }
-//     }
-//     impl Cmd {
-//         pub fn new() -> Self {
-//             Self {}
-//         }
-//     }
-
-//     let mut cmd: Command<Cmd> = Command::new(Cmd::new());
-//     cmd.execute();
-
-//     // expect(() => command.execute() as boolean).toThrow(new Error("Cmd err"));
-//     // expect(command.getStatus()).toBe("executed");
-// }
+#[test]
+fn transition_created_to_executed_illegal() {
+    let mut cmd = Command::new(StubCmd::default());
+    assert_eq!(
+        cmd.transition_to(CmdStatus::Executed),
+        Err(IllegalTransition {
+            from: CmdStatus::Created,
+            to: CmdStatus::Executed
+        })
+    );
+    assert_eq!(cmd.get_status(), CmdStatus::Created);
+    assert_eq!(cmd.child.exec, 0);
+}
+
+#[test]
+fn transition_executed_to_executed_illegal() {
+    let mut cmd = Command::new(StubCmd::default());
+    cmd.execute();
+    assert!(cmd.transition_to(CmdStatus::Executed).is_err());
+    assert_eq!(cmd.child.exec, 1);
+}
+
+#[test]
+fn transition_executed_to_done() {
+    let mut cmd = Command::new(StubCmd::default());
+    cmd.execute();
+    assert_eq!(cmd.transition_to(CmdStatus::Done), Ok(()));
+    assert!(cmd.is_done());
+}
+
+#[test]
+fn transition_flushed_to_created_illegal() {
+    let mut cmd = Command::new(StubCmd::default());
+    cmd.flush();
+    assert_eq!(
+        cmd.transition_to(CmdStatus::Created),
+        Err(IllegalTransition {
+            from: CmdStatus::Flushed,
+            to: CmdStatus::Created
+        })
+    );
+    assert_eq!(cmd.get_status(), CmdStatus::Flushed);
+}
+
+#[test]
+fn transition_cancelled_to_done_illegal() {
+    let mut cmd = Command::new(StubCmd::default());
+    cmd.cancel();
+    assert!(cmd.transition_to(CmdStatus::Done).is_err());
+    assert_eq!(cmd.get_status(), CmdStatus::Cancelled);
+}
+
+#[test]
+fn transition_done_to_executed_illegal() {
+    let mut cmd = Command::new(StubCmd::default());
+    cmd.done();
+    assert!(cmd.transition_to(CmdStatus::Executed).is_err());
+    assert!(cmd.is_done());
+}
+
+#[test]
+fn transition_to_created_always_illegal() {
+    let mut cmd = Command::new(StubCmd::default());
+    assert!(cmd.transition_to(CmdStatus::Created).is_err());
+}
+
+#[test]
+fn transition_any_to_cancelled_or_flushed() {
+    let all = [
+        CmdStatus::Cancelled,
+        CmdStatus::Created,
+        CmdStatus::Done,
+        CmdStatus::Executed,
+        CmdStatus::Flushed,
+    ];
+    for status in all {
+        assert!(status.can_transition_to(CmdStatus::Cancelled));
+        assert!(status.can_transition_to(CmdStatus::Flushed));
+    }
+}
+
+#[test]
+fn illegal_transition_message() {
+    let error = IllegalTransition {
+        from: CmdStatus::Flushed,
+        to: CmdStatus::Done,
+    };
+    assert_eq!(error.to_string(), "a command cannot go from Flushed to Done");
+}
+
+struct UndoableStubCmd {
+    value: Rc<Cell<i32>>,
+}
+
+impl CommandBase for UndoableStubCmd {}
+
+impl CustomCmd for UndoableStubCmd {
+    type Error = Infallible;
+
+    fn execution(&mut self) -> Result<(), Self::Error> {
+        self.value.set(self.value.get() + 1);
+        Ok(())
+    }
+}
+
+impl Undoable for UndoableStubCmd {
+    fn undo(&mut self) {
+        self.value.set(self.value.get() - 1);
+    }
+
+    fn redo(&mut self) {
+        self.value.set(self.value.get() + 1);
+    }
+
+    fn get_undo_name(&self) -> String {
+        "incr".to_string()
+    }
+}
+
+#[test]
+fn done_adds_undoable_command_to_history() {
+    let value = Rc::new(Cell::new(0));
+    let history = HistoryHandle::new(LinearHistoryImpl::new());
+    let mut cmd = Command::new(UndoableStubCmd {
+        value: value.clone(),
+    });
+    cmd.set_history(history.clone());
+    assert!(cmd.has_history());
+    cmd.execute();
+    cmd.done();
+    assert!(cmd.is_done());
+    assert!(cmd.child.is_in_history());
+    assert!(!cmd.has_history());
+    assert_eq!(
+        history.borrow().get_last_undo().unwrap().get_undo_name(),
+        "incr"
+    );
+    history.undo();
+    assert_eq!(value.get(), 0);
+    history.redo();
+    assert_eq!(value.get(), 1);
+}
+
+#[test]
+fn done_without_history_keeps_child() {
+    let mut cmd = Command::new(UndoableStubCmd {
+        value: Rc::new(Cell::new(0)),
+    });
+    cmd.execute();
+    cmd.done();
+    assert!(cmd.is_done());
+    assert!(!cmd.child.is_in_history());
+    assert_eq!(cmd.child.value.get(), 1);
+}
+
+#[test]
+fn cancelled_command_not_added_to_history() {
+    let history = HistoryHandle::new(LinearHistoryImpl::new());
+    let mut cmd = Command::new(UndoableStubCmd {
+        value: Rc::new(Cell::new(0)),
+    });
+    cmd.set_history(history.clone());
+    cmd.execute();
+    cmd.cancel();
+    cmd.done();
+    assert_eq!(cmd.get_status(), CmdStatus::Cancelled);
+    assert!(!cmd.child.is_in_history());
+    assert!(!history.can_undo());
+}
+
+#[test]
+#[should_panic(expected = "given to its undo history")]
+fn child_in_history_not_accessible() {
+    let history = HistoryHandle::new(LinearHistoryImpl::new());
+    let mut cmd = Command::new(UndoableStubCmd {
+        value: Rc::new(Cell::new(0)),
+    });
+    cmd.set_history(history);
+    cmd.execute();
+    cmd.done();
+    cmd.child.value.get();
+}
+
+#[test]
+fn child_in_history_cannot_execute() {
+    let history = HistoryHandle::new(LinearHistoryImpl::new());
+    let mut cmd = Command::new(UndoableStubCmd {
+        value: Rc::new(Cell::new(0)),
+    });
+    cmd.set_history(history);
+    cmd.execute();
+    cmd.done();
+    assert!(!cmd.can_execute());
+    assert!(matches!(cmd.execute(), CmdOutcome::NotExecutable));
+}
+
+struct FailingCmd {
+    exec: u32,
+    mementos: u32,
+}
+
+impl CommandBase for FailingCmd {
+    fn create_memento(&mut self) {
+        self.mementos += 1;
+    }
+}
+
+impl CustomCmd for FailingCmd {
+    type Error = String;
+
+    fn execution(&mut self) -> Result<(), Self::Error> {
+        self.exec += 1;
+        Err("Cmd err".to_string())
+    }
+}
+
+fn failing_cmd() -> Command<FailingCmd> {
+    Command::new(FailingCmd {
+        exec: 0,
+        mementos: 0,
+    })
+}
+
+#[test]
+fn failure_in_execution_gives_error() {
+    let mut cmd = failing_cmd();
+    assert_eq!(cmd.execute(), CmdOutcome::Failed("Cmd err".to_string()));
+}
+
+#[test]
+fn failure_in_execution_command_executed() {
+    let mut cmd = failing_cmd();
+    cmd.execute();
+    assert_eq!(cmd.get_status(), CmdStatus::Executed);
+}
+
+#[test]
+fn failure_in_execution_can_be_executed_again() {
+    let mut cmd = failing_cmd();
+    cmd.execute();
+    assert_eq!(cmd.execute(), CmdOutcome::Failed("Cmd err".to_string()));
+    assert_eq!(cmd.child.exec, 2);
+    assert_eq!(cmd.child.mementos, 1);
+}
+
+#[test]
+fn failure_in_execution_can_be_done() {
+    let mut cmd = failing_cmd();
+    cmd.execute();
+    cmd.done();
+    assert!(cmd.is_done());
+}
+
+#[test]
+fn outcome_is_executed() {
+    assert!(CmdOutcome::<()>::Executed.is_executed());
+    assert!(!CmdOutcome::<()>::NotExecutable.is_executed());
+    assert!(!CmdOutcome::Failed(()).is_executed());
+}
+
+struct CrashCmd;
+
+impl CommandBase for CrashCmd {}
+
+impl CustomCmd for CrashCmd {
+    type Error = Infallible;
+
+    fn execution(&mut self) -> Result<(), Self::Error> {
+        panic!("Cmd err")
+    }
+}
+
+#[test]
+fn catch_panics_disabled_by_default() {
+    assert!(!Command::new(CrashCmd).is_catching_panics());
+}
+
+#[test]
+#[should_panic(expected = "Cmd err")]
+fn crash_in_execution_not_caught() {
+    let mut cmd = Command::new(CrashCmd);
+    cmd.execute();
+}
+
+#[test]
+fn crash_in_execution_command_executed() {
+    let mut cmd = Command::new(CrashCmd);
+    cmd.set_catch_panics(true);
+    assert_eq!(cmd.execute(), CmdOutcome::Panicked("Cmd err".to_string()));
+    assert_eq!(cmd.get_status(), CmdStatus::Executed);
+}
+
+#[test]
+fn crash_in_execution_formatted_message() {
+    struct Cmd;
+    impl CommandBase for Cmd {}
+
+    impl CustomCmd for Cmd {
+        type Error = Infallible;
+
+        fn execution(&mut self) -> Result<(), Self::Error> {
+            panic!("Cmd err {}", 1)
+        }
+    }
+
+    let mut cmd = Command::new(Cmd);
+    cmd.set_catch_panics(true);
+    assert_eq!(cmd.execute(), CmdOutcome::Panicked("Cmd err 1".to_string()));
+}
+
+#[test]
+fn crash_in_execution_command_can_be_cancelled() {
+    let mut cmd = Command::new(CrashCmd);
+    cmd.set_catch_panics(true);
+    cmd.execute();
+    cmd.cancel();
+    assert_eq!(cmd.get_status(), CmdStatus::Cancelled);
+}
+
+#[test]
+fn no_crash_with_catch_panics() {
+    let mut cmd = Command::new(StubCmd::default());
+    cmd.set_catch_panics(true);
+    assert_eq!(cmd.execute(), CmdOutcome::Executed);
+    assert_eq!(cmd.child.exec, 1);
+}
@@ -0,0 +1,305 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{
+    command::{CommandBase, CustomCmd},
+    dnd::{CancellableDnD, DnD},
+    event::{KeyEvent, Modifiers, MouseButton, PointerEvent, UIEvent},
+    fsm::{execute_continuously, execute_continuously_in},
+    historyhandle::HistoryHandle,
+    interaction::{Interaction, SrcTgtPointsData},
+    linearhistory::LinearHistoryImpl,
+    subject::Subscription,
+    undoble::Undoable,
+};
+
+use super::interaction::{cancelled, down, drag, mv, record, started, stopped, up};
+
+fn escape() -> UIEvent {
+    UIEvent::KeyDown(KeyEvent::new(KeyEvent::ESCAPE))
+}
+
+#[test]
+fn dnd() {
+    let mut dnd = DnD::new();
+    let (steps, _sub) = record(&dnd);
+    dnd.process_event(&down(1.0, 2.0));
+    dnd.process_event(&drag(3.0, 5.0));
+    assert_eq!(dnd.get_data().get_diff_x(), 2.0);
+    assert_eq!(dnd.get_data().get_diff_y(), 3.0);
+    dnd.process_event(&up(4.0, 7.0));
+    let data = stopped(&steps).unwrap();
+    assert_eq!((data.src.x, data.src.y), (1.0, 2.0));
+    assert_eq!((data.tgt.x, data.tgt.y), (4.0, 7.0));
+    assert!(!dnd.is_running());
+}
+
+#[test]
+fn dnd_modifiers() {
+    let shift = Modifiers {
+        shift: true,
+        ..Modifiers::NONE
+    };
+    let mut dnd = DnD::new();
+    dnd.process_event(&down(1.0, 2.0));
+    dnd.process_event(&UIEvent::PointerMove(
+        PointerEvent::new(3.0, 5.0, MouseButton::Primary)
+            .with_buttons(MouseButton::Primary)
+            .with_modifiers(shift),
+    ));
+    assert_eq!(dnd.get_data().get_modifiers(), shift);
+}
+
+#[test]
+fn hover_move_does_not_drag() {
+    let mut dnd = DnD::new();
+    let (steps, _sub) = record(&dnd);
+    dnd.process_event(&down(1.0, 2.0));
+    assert!(!dnd.process_event(&mv(3.0, 5.0)));
+    assert!(!dnd.is_running());
+    dnd.process_event(&up(1.0, 2.0));
+    assert!(!started(&steps));
+    assert!(stopped(&steps).is_none());
+}
+
+#[test]
+fn press_does_not_start_dnd() {
+    let mut dnd = DnD::new();
+    let (steps, _sub) = record(&dnd);
+    dnd.process_event(&down(1.0, 2.0));
+    assert!(!dnd.is_running());
+    assert!(steps.borrow().is_empty());
+    dnd.process_event(&drag(2.0, 2.0));
+    assert!(dnd.is_running());
+    assert!(started(&steps));
+}
+
+#[test]
+fn release_without_move_does_not_start() {
+    let mut dnd = DnD::new();
+    let (steps, _sub) = record(&dnd);
+    dnd.process_event(&down(1.0, 2.0));
+    dnd.process_event(&up(1.0, 2.0));
+    assert!(!started(&steps));
+    assert!(!cancelled(&steps));
+    assert!(stopped(&steps).is_none());
+    assert!(!dnd.is_running());
+}
+
+#[test]
+fn escape_does_not_cancel_dnd() {
+    let mut dnd = DnD::new();
+    dnd.process_event(&down(1.0, 2.0));
+    dnd.process_event(&drag(3.0, 5.0));
+    assert!(!dnd.process_event(&escape()));
+    assert!(dnd.is_running());
+}
+
+#[test]
+fn escape_cancels_cancellable_dnd() {
+    let mut dnd = CancellableDnD::new();
+    let (steps, _sub) = record(&dnd);
+    dnd.process_event(&down(1.0, 2.0));
+    dnd.process_event(&drag(3.0, 5.0));
+    assert!(dnd.process_event(&escape()));
+    assert!(cancelled(&steps));
+    dnd.process_event(&up(3.0, 5.0));
+    assert!(stopped(&steps).is_none());
+}
+
+#[test]
+fn cancellable_dnd() {
+    let mut dnd = CancellableDnD::new();
+    let (steps, _sub) = record(&dnd);
+    dnd.process_event(&down(1.0, 2.0));
+    dnd.process_event(&drag(3.0, 5.0));
+    dnd.process_event(&up(3.0, 5.0));
+    assert!(stopped(&steps).is_some());
+}
+
+/// Moves a shape: executed again on each move with the new delta.
+struct MoveShape {
+    shape: Rc<RefCell<(f64, f64)>>,
+    origin: (f64, f64),
+    delta: (f64, f64),
+    executions: Rc<RefCell<usize>>,
+}
+
+impl CommandBase for MoveShape {}
+
+impl CustomCmd for MoveShape {
+    type Error = String;
+
+    fn execution(&mut self) -> Result<(), Self::Error> {
+        *self.executions.borrow_mut() += 1;
+        *self.shape.borrow_mut() = (self.origin.0 + self.delta.0, self.origin.1 + self.delta.1);
+        Ok(())
+    }
+}
+
+impl Undoable for MoveShape {
+    fn undo(&mut self) {
+        *self.shape.borrow_mut() = self.origin;
+    }
+
+    fn redo(&mut self) {
+        *self.shape.borrow_mut() = (self.origin.0 + self.delta.0, self.origin.1 + self.delta.1);
+    }
+
+    fn get_undo_name(&self) -> String {
+        "Move".to_string()
+    }
+}
+
+struct Editor {
+    shape: Rc<RefCell<(f64, f64)>>,
+    executions: Rc<RefCell<usize>>,
+    history: HistoryHandle,
+}
+
+fn bind<I: Interaction<Data = SrcTgtPointsData>>(interaction: &I) -> (Editor, Subscription) {
+    let editor = Editor {
+        shape: Rc::new(RefCell::new((10.0, 10.0))),
+        executions: Rc::default(),
+        history: HistoryHandle::new(LinearHistoryImpl::new()).into(),
+    };
+    let shape = editor.shape.clone();
+    let executions = editor.executions.clone();
+    let subscription = interaction.subscribe(execute_continuously_in(
+        move |_: &SrcTgtPointsData| MoveShape {
+            shape: shape.clone(),
+            origin: *shape.borrow(),
+            delta: (0.0, 0.0),
+            executions: executions.clone(),
+        },
+        |cmd, data| cmd.delta = (data.get_diff_x(), data.get_diff_y()),
+        editor.history.clone(),
+    ));
+    (editor, subscription)
+}
+
+#[test]
+fn command_executed_on_each_move() {
+    let mut dnd = DnD::new();
+    let (editor, _sub) = bind(&dnd);
+    dnd.process_event(&down(0.0, 0.0));
+    dnd.process_event(&drag(1.0, 1.0));
+    assert_eq!(*editor.shape.borrow(), (11.0, 11.0));
+    dnd.process_event(&drag(5.0, 2.0));
+    assert_eq!(*editor.shape.borrow(), (15.0, 12.0));
+    let executions = *editor.executions.borrow();
+    dnd.process_event(&up(6.0, 2.0));
+    assert_eq!(*editor.executions.borrow(), executions + 1);
+    assert_eq!(*editor.shape.borrow(), (16.0, 12.0));
+    assert!(editor.history.can_undo());
+    editor.history.undo();
+    assert_eq!(*editor.shape.borrow(), (10.0, 10.0));
+    editor.history.redo();
+    assert_eq!(*editor.shape.borrow(), (16.0, 12.0));
+}
+
+#[test]
+fn press_alone_does_not_execute_command() {
+    let mut dnd = CancellableDnD::new();
+    let (editor, _sub) = bind(&dnd);
+    dnd.process_event(&down(0.0, 0.0));
+    assert_eq!(*editor.executions.borrow(), 0);
+    dnd.process_event(&escape());
+    dnd.process_event(&down(0.0, 0.0));
+    dnd.process_event(&up(0.0, 0.0));
+    assert_eq!(*editor.executions.borrow(), 0);
+    assert_eq!(*editor.shape.borrow(), (10.0, 10.0));
+    assert!(!editor.history.can_undo());
+}
+
+#[test]
+fn cancelled_drag_undoes_command() {
+    let mut dnd = CancellableDnD::new();
+    let (editor, _sub) = bind(&dnd);
+    dnd.process_event(&down(0.0, 0.0));
+    dnd.process_event(&drag(3.0, 3.0));
+    assert_eq!(*editor.shape.borrow(), (13.0, 13.0));
+    dnd.process_event(&escape());
+    assert_eq!(*editor.shape.borrow(), (10.0, 10.0));
+    assert!(!editor.history.can_undo());
+}
+
+#[test]
+fn successive_drags() {
+    let mut dnd = DnD::new();
+    let (editor, _sub) = bind(&dnd);
+    for _ in 0..2 {
+        dnd.process_event(&down(0.0, 0.0));
+        dnd.process_event(&drag(1.0, 0.0));
+        dnd.process_event(&up(1.0, 0.0));
+    }
+    assert_eq!(*editor.shape.borrow(), (12.0, 10.0));
+    editor.history.undo();
+    assert_eq!(*editor.shape.borrow(), (11.0, 10.0));
+}
+
+type Preview = Rc<RefCell<Option<(f64, f64)>>>;
+
+/// Previews the position of a shape during a drag: not undoable.
+struct PreviewMove {
+    preview: Preview,
+    delta: (f64, f64),
+}
+
+impl CommandBase for PreviewMove {}
+
+impl CustomCmd for PreviewMove {
+    type Error = String;
+
+    fn execution(&mut self) -> Result<(), Self::Error> {
+        *self.preview.borrow_mut() = Some(self.delta);
+        Ok(())
+    }
+}
+
+fn bind_preview<I: Interaction<Data = SrcTgtPointsData>>(interaction: &I) -> (Preview, Subscription) {
+    let preview: Preview = Rc::default();
+    let target = preview.clone();
+    let subscription = interaction.subscribe(execute_continuously(
+        move |_: &SrcTgtPointsData| PreviewMove { preview: target.clone(), delta: (0.0, 0.0) },
+        |cmd, data| cmd.delta = (data.get_diff_x(), data.get_diff_y()),
+    ));
+    (preview, subscription)
+}
+
+#[test]
+fn command_without_history_executed_on_each_move() {
+    let mut dnd = DnD::new();
+    let (preview, _sub) = bind_preview(&dnd);
+    dnd.process_event(&down(0.0, 0.0));
+    dnd.process_event(&drag(1.0, 1.0));
+    assert_eq!(*preview.borrow(), Some((1.0, 1.0)));
+    dnd.process_event(&drag(4.0, 2.0));
+    assert_eq!(*preview.borrow(), Some((4.0, 2.0)));
+    dnd.process_event(&up(5.0, 2.0));
+    assert_eq!(*preview.borrow(), Some((5.0, 2.0)));
+}
+
+#[test]
+fn cancelled_drag_keeps_command_without_history_effects() {
+    let mut dnd = CancellableDnD::new();
+    let (preview, _sub) = bind_preview(&dnd);
+    dnd.process_event(&down(0.0, 0.0));
+    dnd.process_event(&drag(3.0, 3.0));
+    dnd.process_event(&escape());
+    assert_eq!(*preview.borrow(), Some((3.0, 3.0)));
+    assert!(!dnd.is_running());
+}
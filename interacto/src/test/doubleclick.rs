@@ -0,0 +1,117 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::time::Duration;
+
+use crate::{
+    clock::VirtualClock,
+    doubleclick::DoubleClick,
+    event::{MouseButton, PointerEvent, UIEvent},
+    interaction::Interaction,
+};
+
+use super::interaction::{cancelled, down, drag, mv, record, stopped, up};
+
+fn double_click(clock: &VirtualClock) -> DoubleClick {
+    let mut interaction = DoubleClick::with_delay(Duration::from_millis(200));
+    interaction.set_clock(Box::new(clock.clone()));
+    interaction
+}
+
+#[test]
+fn default_delay() {
+    assert_eq!(DoubleClick::new().get_delay(), DoubleClick::DEFAULT_DELAY);
+}
+
+#[test]
+fn double_click_stops() {
+    let clock = VirtualClock::new();
+    let mut interaction = double_click(&clock);
+    let (steps, _sub) = record(&interaction);
+    interaction.process_event(&down(1.0, 2.0));
+    interaction.process_event(&up(1.0, 2.0));
+    clock.advance(Duration::from_millis(150));
+    interaction.process_event(&down(1.0, 2.0));
+    assert!(stopped(&steps).is_none());
+    interaction.process_event(&up(1.0, 2.0));
+    let data = stopped(&steps).unwrap();
+    assert_eq!((data.x, data.y), (1.0, 2.0));
+}
+
+#[test]
+fn too_slow_second_click() {
+    let clock = VirtualClock::new();
+    let mut interaction = double_click(&clock);
+    let (steps, _sub) = record(&interaction);
+    interaction.process_event(&down(1.0, 2.0));
+    interaction.process_event(&up(1.0, 2.0));
+    clock.advance(Duration::from_millis(250));
+    assert!(interaction.check_timeout());
+    assert!(cancelled(&steps));
+    assert!(!interaction.is_running());
+}
+
+#[test]
+fn slow_click_starts_a_new_double_click() {
+    let clock = VirtualClock::new();
+    let mut interaction = double_click(&clock);
+    let (steps, _sub) = record(&interaction);
+    interaction.process_event(&down(1.0, 2.0));
+    interaction.process_event(&up(1.0, 2.0));
+    clock.advance(Duration::from_millis(250));
+    interaction.process_event(&down(1.0, 2.0));
+    interaction.process_event(&up(1.0, 2.0));
+    assert!(cancelled(&steps));
+    assert!(stopped(&steps).is_none());
+    interaction.process_event(&down(1.0, 2.0));
+    interaction.process_event(&up(1.0, 2.0));
+    assert!(stopped(&steps).is_some());
+}
+
+#[test]
+fn move_cancels_double_click() {
+    let clock = VirtualClock::new();
+    let mut interaction = double_click(&clock);
+    let (steps, _sub) = record(&interaction);
+    interaction.process_event(&down(1.0, 2.0));
+    interaction.process_event(&up(1.0, 2.0));
+    interaction.process_event(&mv(5.0, 2.0));
+    assert!(cancelled(&steps));
+}
+
+#[test]
+fn small_moves_do_not_cancel_double_click() {
+    let clock = VirtualClock::new();
+    let mut interaction = double_click(&clock);
+    let (steps, _sub) = record(&interaction);
+    interaction.process_event(&down(1.0, 2.0));
+    interaction.process_event(&drag(2.0, 2.0));
+    interaction.process_event(&up(2.0, 2.0));
+    interaction.process_event(&mv(2.0, 3.0));
+    interaction.process_event(&down(2.0, 3.0));
+    interaction.process_event(&up(2.0, 3.0));
+    assert!(!cancelled(&steps));
+    assert!(stopped(&steps).is_some());
+}
+
+#[test]
+fn other_button_cancels_double_click() {
+    let clock = VirtualClock::new();
+    let mut interaction = double_click(&clock);
+    let (steps, _sub) = record(&interaction);
+    interaction.process_event(&down(1.0, 2.0));
+    interaction.process_event(&up(1.0, 2.0));
+    interaction.process_event(&UIEvent::PointerDown(PointerEvent::new(1.0, 2.0, MouseButton::Secondary)));
+    assert!(cancelled(&steps));
+}
@@ -0,0 +1,211 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::{cell::RefCell, rc::Rc, time::Duration};
+
+use crate::{
+    clock::VirtualClock,
+    command::{CommandBase, CustomCmd},
+    doubleclick::DoubleClick,
+    draglock::DragLock,
+    event::{KeyEvent, UIEvent},
+    fsm::execute_continuously_in,
+    historyhandle::HistoryHandle,
+    interaction::{Interaction, SrcTgtPointsData},
+    linearhistory::LinearHistoryImpl,
+    undoble::Undoable,
+};
+
+use super::interaction::{cancelled, down, drag, mv, record, started, stopped, up};
+
+fn drag_lock(clock: &VirtualClock) -> DragLock {
+    let mut interaction = DragLock::with_delay(Duration::from_millis(200));
+    interaction.set_clock(Box::new(clock.clone()));
+    interaction
+}
+
+fn double_click(interaction: &mut DragLock, x: f64, y: f64) {
+    interaction.process_event(&down(x, y));
+    interaction.process_event(&up(x, y));
+    interaction.process_event(&down(x, y));
+    interaction.process_event(&up(x, y));
+}
+
+#[test]
+fn default_delay() {
+    assert_eq!(DragLock::new().get_delay(), DoubleClick::DEFAULT_DELAY);
+}
+
+#[test]
+fn lock_move_release() {
+    let clock = VirtualClock::new();
+    let mut interaction = drag_lock(&clock);
+    let (steps, _sub) = record(&interaction);
+    double_click(&mut interaction, 1.0, 1.0);
+    assert!(interaction.is_running());
+    interaction.process_event(&mv(4.0, 3.0));
+    assert_eq!(interaction.get_data().get_diff_x(), 3.0);
+    interaction.process_event(&mv(6.0, 5.0));
+    assert!(stopped(&steps).is_none());
+    double_click(&mut interaction, 6.0, 5.0);
+    let data = stopped(&steps).unwrap();
+    assert_eq!((data.src.x, data.src.y), (1.0, 1.0));
+    assert_eq!((data.tgt.x, data.tgt.y), (6.0, 5.0));
+    assert!(!interaction.is_running());
+}
+
+#[test]
+fn locking_double_click_starts_drag_lock() {
+    let clock = VirtualClock::new();
+    let mut interaction = drag_lock(&clock);
+    let (steps, _sub) = record(&interaction);
+    interaction.process_event(&down(1.0, 1.0));
+    interaction.process_event(&up(1.0, 1.0));
+    interaction.process_event(&down(1.0, 1.0));
+    assert!(!interaction.is_running());
+    assert!(steps.borrow().is_empty());
+    interaction.process_event(&up(1.0, 1.0));
+    assert!(interaction.is_running());
+    assert!(started(&steps));
+}
+
+#[test]
+fn small_moves_do_not_cancel_locking_double_click() {
+    let clock = VirtualClock::new();
+    let mut interaction = drag_lock(&clock);
+    interaction.process_event(&down(1.0, 1.0));
+    interaction.process_event(&drag(2.0, 1.0));
+    interaction.process_event(&up(2.0, 1.0));
+    interaction.process_event(&mv(2.0, 2.0));
+    interaction.process_event(&down(2.0, 2.0));
+    interaction.process_event(&up(2.0, 2.0));
+    assert!(interaction.is_running());
+}
+
+#[test]
+fn slow_locking_double_click_does_not_start() {
+    let clock = VirtualClock::new();
+    let mut interaction = drag_lock(&clock);
+    let (steps, _sub) = record(&interaction);
+    interaction.process_event(&down(1.0, 1.0));
+    interaction.process_event(&up(1.0, 1.0));
+    clock.advance(Duration::from_millis(300));
+    assert!(interaction.check_timeout());
+    assert!(!started(&steps));
+    assert!(!cancelled(&steps));
+    assert!(!interaction.is_running());
+    interaction.process_event(&down(1.0, 1.0));
+    interaction.process_event(&up(1.0, 1.0));
+    interaction.process_event(&down(1.0, 1.0));
+    interaction.process_event(&up(1.0, 1.0));
+    assert!(interaction.is_running());
+}
+
+#[test]
+fn slow_releasing_double_click_keeps_lock() {
+    let clock = VirtualClock::new();
+    let mut interaction = drag_lock(&clock);
+    let (steps, _sub) = record(&interaction);
+    double_click(&mut interaction, 1.0, 1.0);
+    interaction.process_event(&mv(4.0, 3.0));
+    interaction.process_event(&down(4.0, 3.0));
+    interaction.process_event(&up(4.0, 3.0));
+    clock.advance(Duration::from_millis(300));
+    interaction.check_timeout();
+    assert!(interaction.is_running());
+    interaction.process_event(&mv(5.0, 3.0));
+    assert_eq!(interaction.get_data().tgt.x, 5.0);
+    assert!(!cancelled(&steps));
+    assert!(stopped(&steps).is_none());
+}
+
+#[test]
+fn escape_cancels_drag_lock() {
+    let clock = VirtualClock::new();
+    let mut interaction = drag_lock(&clock);
+    let (steps, _sub) = record(&interaction);
+    double_click(&mut interaction, 1.0, 1.0);
+    interaction.process_event(&mv(4.0, 3.0));
+    interaction.process_event(&UIEvent::KeyDown(KeyEvent::new(KeyEvent::ESCAPE)));
+    assert!(cancelled(&steps));
+    assert!(!interaction.is_running());
+}
+
+#[test]
+fn drag_move_does_not_move_lock() {
+    let clock = VirtualClock::new();
+    let mut interaction = drag_lock(&clock);
+    double_click(&mut interaction, 1.0, 1.0);
+    assert!(!interaction.process_event(&drag(4.0, 3.0)));
+    assert_eq!(interaction.get_data().tgt.x, 1.0);
+    assert!(interaction.is_running());
+}
+
+/// Counts its executions and undos.
+#[derive(Default)]
+struct Counts {
+    executions: usize,
+    undos: usize,
+}
+
+struct Move(Rc<RefCell<Counts>>);
+
+impl CommandBase for Move {}
+
+impl CustomCmd for Move {
+    type Error = String;
+
+    fn execution(&mut self) -> Result<(), Self::Error> {
+        self.0.borrow_mut().executions += 1;
+        Ok(())
+    }
+}
+
+impl Undoable for Move {
+    fn undo(&mut self) {
+        self.0.borrow_mut().undos += 1;
+    }
+
+    fn redo(&mut self) {
+    }
+
+    fn get_undo_name(&self) -> String {
+        "Move".to_string()
+    }
+}
+
+#[test]
+fn locking_clicks_do_not_execute_command() {
+    let clock = VirtualClock::new();
+    let mut interaction = drag_lock(&clock);
+    let counts = Rc::new(RefCell::new(Counts::default()));
+    let counts2 = counts.clone();
+    let history = HistoryHandle::new(LinearHistoryImpl::new());
+    let _sub = interaction.subscribe(execute_continuously_in(
+        move |_: &SrcTgtPointsData| Move(counts2.clone()),
+        |_, _| {},
+        history.clone(),
+    ));
+    interaction.process_event(&down(1.0, 1.0));
+    interaction.process_event(&up(1.0, 1.0));
+    clock.advance(Duration::from_millis(300));
+    interaction.check_timeout();
+    assert_eq!((counts.borrow().executions, counts.borrow().undos), (0, 0));
+    double_click(&mut interaction, 1.0, 1.0);
+    assert_eq!(counts.borrow().executions, 1);
+    interaction.process_event(&mv(4.0, 3.0));
+    interaction.process_event(&UIEvent::KeyDown(KeyEvent::new(KeyEvent::ESCAPE)));
+    assert_eq!((counts.borrow().executions, counts.borrow().undos), (2, 1));
+    assert!(!history.can_undo());
+}
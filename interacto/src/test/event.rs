@@ -0,0 +1,125 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use crate::event::{
+    ButtonSet, KeyEvent, Modifiers, MouseButton, PointerEvent, TouchEvent, UIEvent, WheelEvent, WidgetId, WidgetValue,
+};
+
+const SHIFT: Modifiers = Modifiers {
+    shift: true,
+    ..Modifiers::NONE
+};
+
+#[test]
+fn no_modifiers() {
+    assert!(Modifiers::default().is_empty());
+    assert!(!SHIFT.is_empty());
+}
+
+#[test]
+fn pointer_event() {
+    let event = PointerEvent::new(1.0, 2.0, MouseButton::Secondary)
+        .with_modifiers(SHIFT)
+        .with_target(WidgetId(3));
+    assert_eq!(event.x, 1.0);
+    assert_eq!(event.y, 2.0);
+    assert_eq!(event.button, MouseButton::Secondary);
+    assert_eq!(event.modifiers, SHIFT);
+    assert_eq!(event.target, Some(WidgetId(3)));
+}
+
+#[test]
+fn pointer_event_without_buttons_held() {
+    assert!(PointerEvent::new(1.0, 2.0, MouseButton::Primary).buttons.is_empty());
+}
+
+#[test]
+fn pointer_event_buttons_held() {
+    let event = PointerEvent::new(1.0, 2.0, MouseButton::Primary)
+        .with_buttons(ButtonSet::from(MouseButton::Primary).with(MouseButton::Other(4)));
+    assert!(event.buttons.contains(MouseButton::Primary));
+    assert!(event.buttons.contains(MouseButton::Other(4)));
+    assert!(!event.buttons.contains(MouseButton::Secondary));
+    assert!(!event.buttons.contains(MouseButton::Other(5)));
+}
+
+#[test]
+fn button_set_insert_remove() {
+    let mut buttons = ButtonSet::EMPTY;
+    buttons.insert(MouseButton::Middle);
+    buttons.insert(MouseButton::Secondary);
+    buttons.remove(MouseButton::Middle);
+    assert!(buttons.contains(MouseButton::Secondary));
+    assert!(!buttons.contains(MouseButton::Middle));
+    buttons.remove(MouseButton::Secondary);
+    assert!(buttons.is_empty());
+}
+
+#[test]
+fn button_set_ignores_untracked_buttons() {
+    let buttons = ButtonSet::from(MouseButton::Other(29));
+    assert!(buttons.is_empty());
+    assert!(!buttons.contains(MouseButton::Other(29)));
+}
+
+#[test]
+fn key_event() {
+    let event = KeyEvent::new("KeyA").with_text("a").with_modifiers(SHIFT);
+    assert_eq!(event.code, "KeyA");
+    assert_eq!(event.text.as_deref(), Some("a"));
+    assert!(!event.repeat);
+    assert!(!event.is_escape());
+    assert!(KeyEvent::new(KeyEvent::ESCAPE).is_escape());
+}
+
+#[test]
+fn event_target() {
+    let widget = WidgetId(1);
+    assert_eq!(UIEvent::PointerDown(PointerEvent::default()).get_target(), None);
+    assert_eq!(
+        UIEvent::TouchMove(TouchEvent::new(0, 1.0, 1.0).with_target(widget)).get_target(),
+        Some(widget)
+    );
+    assert_eq!(UIEvent::FocusOut(widget).get_target(), Some(widget));
+    assert_eq!(
+        UIEvent::ValueChanged {
+            target: widget,
+            value: WidgetValue::Number(0.5)
+        }
+        .get_target(),
+        Some(widget)
+    );
+}
+
+#[test]
+fn event_modifiers() {
+    let wheel = WheelEvent {
+        delta_y: -3.0,
+        modifiers: SHIFT,
+        ..WheelEvent::default()
+    };
+    assert_eq!(UIEvent::Wheel(wheel).get_modifiers(), SHIFT);
+    assert_eq!(UIEvent::KeyUp(KeyEvent::new("KeyA").with_modifiers(SHIFT)).get_modifiers(), SHIFT);
+    assert!(UIEvent::FocusIn(WidgetId(1)).get_modifiers().is_empty());
+}
+
+#[test]
+fn event_kinds() {
+    let pointer = PointerEvent::new(1.0, 1.0, MouseButton::Primary);
+    assert_eq!(UIEvent::PointerMove(pointer).as_pointer(), Some(&pointer));
+    assert!(UIEvent::PointerMove(pointer).as_key().is_none());
+    assert!(UIEvent::KeyDown(KeyEvent::new("Enter")).as_key().is_some());
+    assert_eq!(UIEvent::TouchEnd(TouchEvent::new(4, 0.0, 0.0)).as_touch().map(|touch| touch.id), Some(4));
+    assert!(UIEvent::Wheel(WheelEvent::default()).as_touch().is_none());
+}
@@ -0,0 +1,509 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+    time::Duration,
+};
+
+use crate::{
+    clock::VirtualClock,
+    command::{CommandBase, CustomCmd},
+    fsm::{execute_on_stop, execute_on_stop_in, Fsm, FsmStep, StateId, StateKind},
+    historyhandle::HistoryHandle,
+    linearhistory::LinearHistoryImpl,
+    subject::Subscription,
+    undoble::Undoable,
+};
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+enum Ev {
+    Press(i32),
+    Move(i32),
+    Release,
+    Escape,
+}
+
+#[derive(Default, Debug, PartialEq)]
+struct Data {
+    src: Option<i32>,
+    tgt: Option<i32>,
+}
+
+struct DragFsm {
+    fsm: Fsm<Ev, Data>,
+    pressed: StateId,
+    dragged: StateId,
+}
+
+/// press -> (move)* -> release; escape cancels.
+fn drag_fsm() -> DragFsm {
+    let mut fsm = Fsm::new();
+    let init = fsm.get_init_state();
+    let pressed = fsm.add_std_state("pressed");
+    let dragged = fsm.add_std_state("dragged");
+    let released = fsm.add_terminal_state("released");
+    let cancelled = fsm.add_cancelling_state("cancelled");
+    fsm.add_transition(init, pressed, |e| matches!(e, Ev::Press(_)))
+        .with_action(|e, data: &mut Data| {
+            if let Ev::Press(x) = e {
+                data.src = Some(*x);
+            }
+        });
+    for src in [pressed, dragged] {
+        fsm.add_transition(src, dragged, |e| matches!(e, Ev::Move(_)))
+            .with_guard(|e, data| matches!(e, Ev::Move(x) if Some(*x) != data.src))
+            .with_action(|e, data| {
+                if let Ev::Move(x) = e {
+                    data.tgt = Some(*x);
+                }
+            });
+        fsm.add_transition(src, cancelled, |e| *e == Ev::Escape);
+    }
+    fsm.add_transition(dragged, released, |e| *e == Ev::Release);
+    DragFsm { fsm, pressed, dragged }
+}
+
+fn record(fsm: &Fsm<Ev, Data>) -> (Rc<RefCell<Vec<FsmStep>>>, Subscription) {
+    let steps = Rc::new(RefCell::new(Vec::new()));
+    let steps2 = steps.clone();
+    let subscription = fsm.subscribe(Box::new(move |step, _| steps2.borrow_mut().push(step)));
+    (steps, subscription)
+}
+
+#[test]
+fn init_state() {
+    let fsm = drag_fsm().fsm;
+    assert_eq!(fsm.get_current_state(), fsm.get_init_state());
+    assert_eq!(fsm.get_state(fsm.get_init_state()).get_kind(), StateKind::Init);
+    assert!(!fsm.is_started());
+}
+
+#[test]
+fn states() {
+    let drag = drag_fsm();
+    assert_eq!(drag.fsm.get_state(drag.pressed).get_name(), "pressed");
+    assert_eq!(drag.fsm.get_state(drag.pressed).get_kind(), StateKind::Standard);
+}
+
+#[test]
+fn not_accepted_event() {
+    let mut fsm = drag_fsm().fsm;
+    let (steps, _sub) = record(&fsm);
+    assert!(!fsm.process(&Ev::Release));
+    assert_eq!(fsm.get_current_state(), fsm.get_init_state());
+    assert!(steps.borrow().is_empty());
+}
+
+#[test]
+fn start_and_update() {
+    let drag = drag_fsm();
+    let mut fsm = drag.fsm;
+    let (steps, _sub) = record(&fsm);
+    assert!(fsm.process(&Ev::Press(1)));
+    assert_eq!(fsm.get_current_state(), drag.pressed);
+    assert!(fsm.is_started());
+    assert_eq!(fsm.get_data().src, Some(1));
+    assert_eq!(*steps.borrow(), vec![FsmStep::Started, FsmStep::Updated]);
+}
+
+#[test]
+fn guard_blocks_transition() {
+    let drag = drag_fsm();
+    let mut fsm = drag.fsm;
+    fsm.process(&Ev::Press(1));
+    assert!(!fsm.process(&Ev::Move(1)));
+    assert!(fsm.process(&Ev::Move(2)));
+    assert_eq!(fsm.get_current_state(), drag.dragged);
+}
+
+#[test]
+fn terminal_state_stops_and_reinits() {
+    let mut fsm = drag_fsm().fsm;
+    let data = Rc::new(RefCell::new(None));
+    let data2 = data.clone();
+    let _stop = fsm.subscribe(Box::new(move |step, d: &Data| {
+        if step == FsmStep::Stopped {
+            *data2.borrow_mut() = Some((d.src, d.tgt));
+        }
+    }));
+    let (steps, _sub) = record(&fsm);
+    fsm.process(&Ev::Press(1));
+    fsm.process(&Ev::Move(2));
+    fsm.process(&Ev::Move(3));
+    fsm.process(&Ev::Release);
+    assert_eq!(
+        *steps.borrow(),
+        vec![
+            FsmStep::Started,
+            FsmStep::Updated,
+            FsmStep::Updated,
+            FsmStep::Updated,
+            FsmStep::Stopped,
+            FsmStep::Reinit
+        ]
+    );
+    assert_eq!(*data.borrow(), Some((Some(1), Some(3))));
+    assert_eq!(fsm.get_current_state(), fsm.get_init_state());
+    assert!(!fsm.is_started());
+    assert_eq!(*fsm.get_data(), Data::default());
+}
+
+#[test]
+fn cancelling_state() {
+    let mut fsm = drag_fsm().fsm;
+    let (steps, _sub) = record(&fsm);
+    fsm.process(&Ev::Press(1));
+    fsm.process(&Ev::Escape);
+    assert_eq!(
+        *steps.borrow(),
+        vec![FsmStep::Started, FsmStep::Updated, FsmStep::Cancelled, FsmStep::Reinit]
+    );
+    assert_eq!(fsm.get_current_state(), fsm.get_init_state());
+}
+
+#[test]
+fn starting_state_delays_start() {
+    let drag = drag_fsm();
+    let mut fsm = drag.fsm;
+    fsm.set_starting_state(drag.dragged);
+    assert_eq!(fsm.get_starting_state(), Some(drag.dragged));
+    let (steps, _sub) = record(&fsm);
+    fsm.process(&Ev::Press(1));
+    assert_eq!(fsm.get_current_state(), drag.pressed);
+    assert!(!fsm.is_started());
+    assert!(steps.borrow().is_empty());
+    fsm.process(&Ev::Move(2));
+    assert!(fsm.is_started());
+    fsm.process(&Ev::Release);
+    assert_eq!(
+        *steps.borrow(),
+        vec![FsmStep::Started, FsmStep::Updated, FsmStep::Stopped, FsmStep::Reinit]
+    );
+}
+
+#[test]
+fn cancelled_before_starting_state_not_published() {
+    let drag = drag_fsm();
+    let mut fsm = drag.fsm;
+    fsm.set_starting_state(drag.dragged);
+    let (steps, _sub) = record(&fsm);
+    fsm.process(&Ev::Press(1));
+    fsm.process(&Ev::Escape);
+    assert_eq!(*steps.borrow(), vec![FsmStep::Reinit]);
+    assert_eq!(fsm.get_current_state(), fsm.get_init_state());
+}
+
+#[test]
+fn cancel_started_fsm() {
+    let mut fsm = drag_fsm().fsm;
+    fsm.process(&Ev::Press(1));
+    let (steps, _sub) = record(&fsm);
+    fsm.cancel();
+    assert_eq!(*steps.borrow(), vec![FsmStep::Cancelled, FsmStep::Reinit]);
+    assert!(fsm.get_data().src.is_none());
+}
+
+#[test]
+fn cancel_not_started_fsm() {
+    let mut fsm = drag_fsm().fsm;
+    let (steps, _sub) = record(&fsm);
+    fsm.cancel();
+    assert_eq!(*steps.borrow(), vec![FsmStep::Reinit]);
+}
+
+#[test]
+fn epsilon_transition() {
+    let mut fsm: Fsm<Ev, Data> = Fsm::new();
+    let init = fsm.get_init_state();
+    let pressed = fsm.add_std_state("pressed");
+    let ended = fsm.add_terminal_state("ended");
+    fsm.add_transition(init, pressed, |e| matches!(e, Ev::Press(_)))
+        .with_action(|e, data| {
+            if let Ev::Press(x) = e {
+                data.src = Some(*x);
+            }
+        });
+    fsm.add_epsilon_transition(pressed, ended)
+        .with_guard(|data| data.src == Some(0))
+        .with_action(|data| data.tgt = Some(0));
+    let (steps, _sub) = record(&fsm);
+    fsm.process(&Ev::Press(1));
+    assert_eq!(fsm.get_current_state(), pressed);
+    fsm.cancel();
+    steps.borrow_mut().clear();
+    fsm.process(&Ev::Press(0));
+    assert_eq!(fsm.get_current_state(), init);
+    assert_eq!(
+        *steps.borrow(),
+        vec![FsmStep::Started, FsmStep::Updated, FsmStep::Stopped, FsmStep::Reinit]
+    );
+}
+
+#[test]
+fn unsubscribe() {
+    let mut fsm = drag_fsm().fsm;
+    let (steps, sub) = record(&fsm);
+    sub.unsubscribe();
+    fsm.process(&Ev::Press(1));
+    assert!(steps.borrow().is_empty());
+}
+
+struct Translate {
+    doc: Rc<RefCell<i32>>,
+    dx: i32,
+}
+
+impl CommandBase for Translate {}
+
+impl CustomCmd for Translate {
+    type Error = String;
+
+    fn execution(&mut self) -> Result<(), Self::Error> {
+        *self.doc.borrow_mut() += self.dx;
+        Ok(())
+    }
+}
+
+impl Undoable for Translate {
+    fn undo(&mut self) {
+        *self.doc.borrow_mut() -= self.dx;
+    }
+
+    fn redo(&mut self) {
+        *self.doc.borrow_mut() += self.dx;
+    }
+
+    fn get_undo_name(&self) -> String {
+        "Translate".to_string()
+    }
+}
+
+#[test]
+fn command_executed_on_stop() {
+    let mut fsm = drag_fsm().fsm;
+    let doc = Rc::new(RefCell::new(0));
+    let history = HistoryHandle::new(LinearHistoryImpl::new());
+    let doc2 = doc.clone();
+    let _sub = fsm.subscribe(execute_on_stop_in(
+        move |data: &Data| Translate {
+            doc: doc2.clone(),
+            dx: data.tgt.unwrap() - data.src.unwrap(),
+        },
+        history.clone(),
+    ));
+    fsm.process(&Ev::Press(1));
+    fsm.process(&Ev::Move(5));
+    assert_eq!(*doc.borrow(), 0);
+    fsm.process(&Ev::Release);
+    assert_eq!(*doc.borrow(), 4);
+    history.undo();
+    assert_eq!(*doc.borrow(), 0);
+}
+
+#[test]
+fn command_executed_on_stop_without_history() {
+    let mut fsm = drag_fsm().fsm;
+    let doc = Rc::new(RefCell::new(0));
+    let doc2 = doc.clone();
+    let _sub = fsm.subscribe(execute_on_stop(move |data: &Data| Translate {
+        doc: doc2.clone(),
+        dx: data.tgt.unwrap() - data.src.unwrap(),
+    }));
+    fsm.process(&Ev::Press(1));
+    fsm.process(&Ev::Move(5));
+    fsm.process(&Ev::Release);
+    assert_eq!(*doc.borrow(), 4);
+}
+
+#[test]
+fn no_command_on_cancel() {
+    let mut fsm = drag_fsm().fsm;
+    let doc = Rc::new(RefCell::new(0));
+    let history = HistoryHandle::new(LinearHistoryImpl::new());
+    let doc2 = doc.clone();
+    let _sub = fsm.subscribe(execute_on_stop_in(
+        move |_: &Data| Translate { doc: doc2.clone(), dx: 1 },
+        history.clone(),
+    ));
+    fsm.process(&Ev::Press(1));
+    fsm.process(&Ev::Move(5));
+    fsm.process(&Ev::Escape);
+    assert_eq!(*doc.borrow(), 0);
+    assert!(!history.can_undo());
+}
+
+/// press -> release (terminal) or, after 1s, held (terminal).
+fn long_press_fsm(clock: &VirtualClock) -> (Fsm<Ev, Data>, StateId) {
+    let mut fsm = Fsm::new();
+    fsm.set_clock(Box::new(clock.clone()));
+    let init = fsm.get_init_state();
+    let pressed = fsm.add_std_state("pressed");
+    let released = fsm.add_cancelling_state("released");
+    let held = fsm.add_terminal_state("held");
+    fsm.add_transition(init, pressed, |e| matches!(e, Ev::Press(_)));
+    fsm.add_transition(pressed, released, |e| *e == Ev::Release);
+    fsm.add_timeout_transition(pressed, held, Duration::from_secs(1))
+        .with_action(|data: &mut Data| data.tgt = Some(1));
+    (fsm, pressed)
+}
+
+#[test]
+fn timeout_deadline() {
+    let clock = VirtualClock::new();
+    let (mut fsm, _) = long_press_fsm(&clock);
+    assert!(fsm.get_deadline().is_none());
+    clock.advance(Duration::from_secs(5));
+    fsm.process(&Ev::Press(1));
+    assert_eq!(fsm.get_deadline(), Some(Duration::from_secs(6)));
+}
+
+#[test]
+fn timeout_not_expired() {
+    let clock = VirtualClock::new();
+    let (mut fsm, pressed) = long_press_fsm(&clock);
+    fsm.process(&Ev::Press(1));
+    clock.advance(Duration::from_millis(999));
+    assert!(!fsm.check_timeout());
+    assert_eq!(fsm.get_current_state(), pressed);
+}
+
+#[test]
+fn timeout_fires() {
+    let clock = VirtualClock::new();
+    let (mut fsm, _) = long_press_fsm(&clock);
+    let (steps, _sub) = record(&fsm);
+    let tgt = Rc::new(RefCell::new(None));
+    let tgt2 = tgt.clone();
+    let _stop = fsm.subscribe(Box::new(move |step, data: &Data| {
+        if step == FsmStep::Stopped {
+            *tgt2.borrow_mut() = data.tgt;
+        }
+    }));
+    fsm.process(&Ev::Press(1));
+    clock.advance(Duration::from_secs(1));
+    assert!(fsm.check_timeout());
+    assert_eq!(fsm.get_current_state(), fsm.get_init_state());
+    assert!(fsm.get_deadline().is_none());
+    assert_eq!(*tgt.borrow(), Some(1));
+    assert_eq!(
+        *steps.borrow(),
+        vec![FsmStep::Started, FsmStep::Updated, FsmStep::Stopped, FsmStep::Reinit]
+    );
+}
+
+#[test]
+fn timeout_cleared_when_leaving_state() {
+    let clock = VirtualClock::new();
+    let (mut fsm, _) = long_press_fsm(&clock);
+    fsm.process(&Ev::Press(1));
+    fsm.process(&Ev::Release);
+    assert!(fsm.get_deadline().is_none());
+    clock.advance(Duration::from_secs(2));
+    assert!(!fsm.check_timeout());
+}
+
+#[test]
+fn expired_timeout_fired_before_event() {
+    let clock = VirtualClock::new();
+    let (mut fsm, _) = long_press_fsm(&clock);
+    let (steps, _sub) = record(&fsm);
+    fsm.process(&Ev::Press(1));
+    clock.advance(Duration::from_secs(2));
+    assert!(!fsm.process(&Ev::Release));
+    assert!(steps.borrow().contains(&FsmStep::Stopped));
+    assert!(!steps.borrow().contains(&FsmStep::Cancelled));
+}
+
+#[test]
+fn timeout_guard() {
+    let clock = VirtualClock::new();
+    let mut fsm: Fsm<Ev, Data> = Fsm::new();
+    fsm.set_clock(Box::new(clock.clone()));
+    let init = fsm.get_init_state();
+    let pressed = fsm.add_std_state("pressed");
+    let ended = fsm.add_terminal_state("ended");
+    fsm.add_transition(init, pressed, |e| matches!(e, Ev::Press(_)))
+        .with_action(|e, data| {
+            if let Ev::Press(x) = e {
+                data.src = Some(*x);
+            }
+        });
+    fsm.add_timeout_transition(pressed, ended, Duration::from_millis(10))
+        .with_guard(|data| data.src == Some(0));
+    fsm.process(&Ev::Press(1));
+    clock.advance(Duration::from_secs(1));
+    assert!(!fsm.check_timeout());
+    assert_eq!(fsm.get_current_state(), pressed);
+}
+
+#[test]
+fn timeout_with_failing_guard_stays_pending() {
+    let clock = VirtualClock::new();
+    let mut fsm: Fsm<Ev, Data> = Fsm::new();
+    fsm.set_clock(Box::new(clock.clone()));
+    let init = fsm.get_init_state();
+    let pressed = fsm.add_std_state("pressed");
+    let ended = fsm.add_terminal_state("ended");
+    let ready = Rc::new(Cell::new(false));
+    let ready2 = ready.clone();
+    fsm.add_transition(init, pressed, |e| matches!(e, Ev::Press(_)));
+    fsm.add_timeout_transition(pressed, ended, Duration::from_millis(10)).with_guard(move |_| ready2.get());
+    fsm.process(&Ev::Press(1));
+    clock.advance(Duration::from_secs(1));
+    assert!(!fsm.check_timeout());
+    assert_eq!(fsm.get_deadline(), Some(Duration::from_millis(10)));
+    ready.set(true);
+    assert!(fsm.check_timeout());
+    assert_eq!(fsm.get_current_state(), init);
+}
+
+#[test]
+#[should_panic(expected = "the source of a timeout transition must be a standard state")]
+fn timeout_from_init_state_rejected() {
+    let mut fsm: Fsm<Ev, Data> = Fsm::new();
+    let init = fsm.get_init_state();
+    let pressed = fsm.add_std_state("pressed");
+    fsm.add_timeout_transition(init, pressed, Duration::from_secs(1));
+}
+
+#[test]
+#[should_panic(expected = "the source of a timeout transition must be a standard state")]
+fn timeout_from_terminal_state_rejected() {
+    let mut fsm: Fsm<Ev, Data> = Fsm::new();
+    let init = fsm.get_init_state();
+    let ended = fsm.add_terminal_state("ended");
+    fsm.add_timeout_transition(ended, init, Duration::from_secs(1));
+}
+
+#[test]
+fn timeout_rearmed_on_reentering_state() {
+    let clock = VirtualClock::new();
+    let mut fsm: Fsm<Ev, Data> = Fsm::new();
+    fsm.set_clock(Box::new(clock.clone()));
+    let init = fsm.get_init_state();
+    let pressed = fsm.add_std_state("pressed");
+    let ended = fsm.add_terminal_state("ended");
+    fsm.add_transition(init, pressed, |e| matches!(e, Ev::Press(_)));
+    fsm.add_transition(pressed, pressed, |e| matches!(e, Ev::Move(_)));
+    fsm.add_timeout_transition(pressed, ended, Duration::from_secs(1));
+    fsm.process(&Ev::Press(1));
+    clock.advance(Duration::from_millis(800));
+    fsm.process(&Ev::Move(2));
+    clock.advance(Duration::from_millis(800));
+    assert!(!fsm.check_timeout());
+    clock.advance(Duration::from_millis(200));
+    assert!(fsm.check_timeout());
+}
@@ -0,0 +1,175 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{
+    historyhandle::HistoryHandle,
+    linearhistory::LinearHistoryImpl,
+    treehistory::TreeUndoHistory,
+    undoble::Undoable,
+    undohistory::{UndoHistory, UndoHistoryBase},
+};
+
+/// Appends a character to a shared document.
+struct Append {
+    c: char,
+    doc: Rc<RefCell<String>>,
+}
+
+impl Undoable for Append {
+    fn undo(&mut self) {
+        self.doc.borrow_mut().pop();
+    }
+
+    fn redo(&mut self) {
+        self.doc.borrow_mut().push(self.c);
+    }
+
+    fn get_undo_name(&self) -> String {
+        format!("Append {}", self.c)
+    }
+}
+
+fn append(c: char, doc: &Rc<RefCell<String>>) -> Box<Append> {
+    doc.borrow_mut().push(c);
+    Box::new(Append { c, doc: doc.clone() })
+}
+
+#[test]
+fn group_is_one_undo_step() {
+    let doc = Rc::default();
+    let mut history = LinearHistoryImpl::new();
+    history.begin_group("Paste");
+    assert!(history.is_grouping());
+    history.add(append('a', &doc));
+    history.add(append('b', &doc));
+    assert!(history.get_undo().is_empty());
+    history.end_group();
+    assert!(!history.is_grouping());
+    assert_eq!(history.get_undo().len(), 1);
+    assert_eq!(history.get_last_undo_message(), Some("Paste".to_string()));
+    history.undo();
+    assert_eq!(*doc.borrow(), "");
+    history.redo();
+    assert_eq!(*doc.borrow(), "ab");
+}
+
+#[test]
+fn empty_group_not_added() {
+    let mut history = LinearHistoryImpl::new();
+    history.begin_group("Paste");
+    history.end_group();
+    assert!(history.get_undo().is_empty());
+}
+
+#[test]
+fn end_without_group_does_nothing() {
+    let mut history = LinearHistoryImpl::new();
+    history.end_group();
+    history.abandon_group();
+    assert!(!history.is_grouping());
+    assert!(history.get_undo().is_empty());
+}
+
+#[test]
+fn nested_groups() {
+    let doc = Rc::default();
+    let mut history = LinearHistoryImpl::new();
+    history.begin_group("Outer");
+    history.add(append('a', &doc));
+    history.begin_group("Inner");
+    history.add(append('b', &doc));
+    history.add(append('c', &doc));
+    history.end_group();
+    history.add(append('d', &doc));
+    assert!(history.is_grouping());
+    history.end_group();
+    assert_eq!(history.get_undo().len(), 1);
+    assert_eq!(history.get_last_undo().unwrap().get_undo_name(), "Outer");
+    history.undo();
+    assert_eq!(*doc.borrow(), "");
+    history.redo();
+    assert_eq!(*doc.borrow(), "abcd");
+}
+
+#[test]
+fn abandon_group_rolls_back() {
+    let doc = Rc::default();
+    let mut history = LinearHistoryImpl::new();
+    history.add(append('a', &doc));
+    history.begin_group("Paste");
+    history.add(append('b', &doc));
+    history.add(append('c', &doc));
+    history.abandon_group();
+    assert_eq!(*doc.borrow(), "a");
+    assert!(!history.is_grouping());
+    assert_eq!(history.get_undo().len(), 1);
+    assert_eq!(history.get_last_undo_message(), Some("Append a".to_string()));
+}
+
+#[test]
+fn abandon_nested_group_keeps_outer_group() {
+    let doc = Rc::default();
+    let mut history = LinearHistoryImpl::new();
+    history.begin_group("Outer");
+    history.add(append('a', &doc));
+    history.begin_group("Inner");
+    history.add(append('b', &doc));
+    history.abandon_group();
+    assert!(history.is_grouping());
+    history.end_group();
+    assert_eq!(*doc.borrow(), "a");
+    history.undo();
+    assert_eq!(*doc.borrow(), "");
+}
+
+#[test]
+fn clear_discards_groups() {
+    let doc = Rc::default();
+    let mut history = LinearHistoryImpl::new();
+    history.begin_group("Paste");
+    history.add(append('a', &doc));
+    history.clear();
+    assert!(!history.is_grouping());
+    history.end_group();
+    assert!(history.get_undo().is_empty());
+}
+
+#[test]
+fn tree_group_is_one_node() {
+    let doc = Rc::default();
+    let mut history = TreeUndoHistory::new();
+    history.begin_group("Paste");
+    history.add(append('a', &doc));
+    history.add(append('b', &doc));
+    history.end_group();
+    assert_eq!(history.get_nodes().len(), 1);
+    history.undo();
+    assert_eq!(*doc.borrow(), "");
+}
+
+#[test]
+fn handle_groups() {
+    let doc = Rc::default();
+    let handle = HistoryHandle::new(LinearHistoryImpl::new());
+    handle.begin_group("Paste");
+    handle.add(append('a', &doc));
+    handle.end_group();
+    handle.begin_group("Paste");
+    handle.add(append('b', &doc));
+    handle.abandon_group();
+    assert_eq!(*doc.borrow(), "a");
+    assert!(handle.can_undo());
+}
@@ -0,0 +1,192 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
+
+use crate::{
+    command::CommandBase,
+    historyhandle::HistoryHandle,
+    linearhistory::LinearHistoryImpl,
+    undo::{Redo, Undo},
+    undoble::Undoable,
+    undohistory::{UndoHistory, UndoHistoryBase},
+};
+
+struct StubUndoable {
+    value: Rc<Cell<i32>>,
+}
+
+impl Undoable for StubUndoable {
+    fn undo(&mut self) {
+        self.value.set(self.value.get() - 1);
+    }
+
+    fn redo(&mut self) {
+        self.value.set(self.value.get() + 1);
+    }
+
+    fn get_undo_name(&self) -> String {
+        "incr".to_string()
+    }
+}
+
+fn history() -> HistoryHandle<LinearHistoryImpl> {
+    HistoryHandle::new(LinearHistoryImpl::new())
+}
+
+#[test]
+fn clones_share_the_history() {
+    let handle = history();
+    let other = handle.clone();
+    handle.add(Box::new(StubUndoable { value: Rc::new(Cell::new(1)) }));
+    assert!(other.can_undo());
+    assert!(handle.ptr_eq(&other));
+}
+
+#[test]
+fn erased_handle_shares_the_history() {
+    let handle = history();
+    let erased: HistoryHandle = handle.clone().into();
+    erased.add(Box::new(StubUndoable { value: Rc::new(Cell::new(1)) }));
+    assert!(handle.ptr_eq(&erased));
+    assert!(handle.erase().ptr_eq(&handle));
+    assert_eq!(handle.borrow().get_undo().len(), 1);
+}
+
+#[test]
+fn typed_handle_keeps_specific_operations() {
+    let handle = history();
+    handle.borrow_mut().set_size_max(1);
+    handle.add(Box::new(StubUndoable { value: Rc::new(Cell::new(1)) }));
+    handle.add(Box::new(StubUndoable { value: Rc::new(Cell::new(1)) }));
+    assert_eq!(handle.borrow().get_size_max(), 1);
+    assert_eq!(handle.borrow().get_undo().len(), 1);
+}
+
+#[test]
+fn distinct_handles_do_not_share() {
+    assert!(!history().ptr_eq(&history()));
+}
+
+#[test]
+fn cannot_undo_redo_when_empty() {
+    let handle = history();
+    assert!(!handle.can_undo());
+    assert!(!handle.can_redo());
+}
+
+#[test]
+fn undo_command_undoes_added_undoable() {
+    let value = Rc::new(Cell::new(1));
+    let handle = history();
+    handle.add(Box::new(StubUndoable { value: value.clone() }));
+    let mut undo = Undo::new(handle.clone()).as_command();
+    assert!(undo.execute().is_executed());
+    assert_eq!(value.get(), 0);
+    assert!(!handle.can_undo());
+    assert!(handle.can_redo());
+}
+
+#[test]
+fn redo_command_redoes_undone_undoable() {
+    let value = Rc::new(Cell::new(1));
+    let handle = history();
+    handle.add(Box::new(StubUndoable { value: value.clone() }));
+    assert!(Undo::new(handle.clone()).as_command().execute().is_executed());
+    assert!(Redo::new(handle.clone()).as_command().execute().is_executed());
+    assert_eq!(value.get(), 1);
+    assert!(handle.can_undo());
+    assert!(!handle.can_redo());
+}
+
+#[test]
+fn undo_command_cannot_execute_once_history_is_empty() {
+    let handle = history();
+    handle.add(Box::new(StubUndoable { value: Rc::new(Cell::new(1)) }));
+    let mut undo = Undo::new(handle.clone()).as_command();
+    assert!(undo.execute().is_executed());
+    assert!(!undo.execute().is_executed());
+}
+
+#[test]
+fn clear_through_handle() {
+    let handle = history();
+    handle.add(Box::new(StubUndoable { value: Rc::new(Cell::new(1)) }));
+    handle.clone().clear();
+    assert!(!handle.can_undo());
+    assert!(handle.borrow().get_last_undo().is_none());
+}
+
+#[test]
+fn toolbar_follows_history() {
+    let handle = history();
+    let undo_label: Rc<RefCell<Option<String>>> = Rc::default();
+    let can_redo = Rc::new(Cell::new(false));
+    let label = undo_label.clone();
+    let redo_enabled = can_redo.clone();
+    let _sub_undos = handle.subscribe_undos(move |u| *label.borrow_mut() = u.map(|u| u.get_undo_name()));
+    let _sub_redos = handle.subscribe_redos(move |u| redo_enabled.set(u.is_some()));
+
+    handle.add(Box::new(StubUndoable { value: Rc::new(Cell::new(1)) }));
+    assert_eq!(undo_label.borrow().as_deref(), Some("incr"));
+    assert!(!can_redo.get());
+
+    assert!(Undo::new(handle.clone()).as_command().execute().is_executed());
+    assert!(undo_label.borrow().is_none());
+    assert!(can_redo.get());
+}
+
+#[test]
+fn observers_can_read_the_history() {
+    let handle = history();
+    let messages: Rc<RefCell<Vec<Option<String>>>> = Rc::default();
+    let observed = messages.clone();
+    let reader = handle.clone();
+    let _sub = handle.subscribe_undos(move |_| {
+        observed.borrow_mut().push(reader.borrow().get_last_undo_message());
+    });
+
+    handle.add(Box::new(StubUndoable { value: Rc::new(Cell::new(1)) }));
+    handle.undo();
+    assert_eq!(*messages.borrow(), vec![Some("incr".to_string()), None]);
+}
+
+#[test]
+fn changes_published_when_mutable_borrow_released() {
+    let handle = history();
+    let count = Rc::new(Cell::new(0));
+    let observed = count.clone();
+    let _sub = handle.subscribe_undos(move |_| observed.set(observed.get() + 1));
+    {
+        let mut history = handle.borrow_mut();
+        history.add(Box::new(StubUndoable { value: Rc::new(Cell::new(1)) }));
+        history.add(Box::new(StubUndoable { value: Rc::new(Cell::new(1)) }));
+        assert_eq!(count.get(), 0);
+    }
+    assert_eq!(count.get(), 1);
+}
+
+#[test]
+fn clones_share_the_observers() {
+    let handle = history();
+    let count = Rc::new(Cell::new(0));
+    let observed = count.clone();
+    let _sub = handle.erase().subscribe_redos(move |_| observed.set(observed.get() + 1));
+    handle.add(Box::new(StubUndoable { value: Rc::new(Cell::new(1)) }));
+    handle.clone().undo();
+    assert_eq!(count.get(), 1);
+}
@@ -0,0 +1,140 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{
+    event::{Modifiers, MouseButton, PointerEvent, UIEvent, WidgetId},
+    fsm::FsmStep,
+    interaction::{Interaction, PointData, SrcTgtPointsData},
+    press::Press,
+    subject::Subscription,
+};
+
+pub(crate) type Steps<D> = Rc<RefCell<Vec<(FsmStep, D)>>>;
+
+pub(crate) fn down(x: f64, y: f64) -> UIEvent {
+    UIEvent::PointerDown(PointerEvent::new(x, y, MouseButton::Primary).with_buttons(MouseButton::Primary))
+}
+
+pub(crate) fn up(x: f64, y: f64) -> UIEvent {
+    UIEvent::PointerUp(PointerEvent::new(x, y, MouseButton::Primary))
+}
+
+/// A move of the pointer without any button held down.
+pub(crate) fn mv(x: f64, y: f64) -> UIEvent {
+    UIEvent::PointerMove(PointerEvent::new(x, y, MouseButton::Primary))
+}
+
+/// A move of the pointer with the primary button held down.
+pub(crate) fn drag(x: f64, y: f64) -> UIEvent {
+    UIEvent::PointerMove(PointerEvent::new(x, y, MouseButton::Primary).with_buttons(MouseButton::Primary))
+}
+
+/// Records the steps of an interaction, with a copy of its data.
+pub(crate) fn record<I: Interaction>(interaction: &I) -> (Steps<I::Data>, Subscription)
+where
+    I::Data: Clone,
+{
+    let steps = Rc::new(RefCell::new(Vec::new()));
+    let steps2 = steps.clone();
+    let subscription = interaction.subscribe(Box::new(move |step, data: &I::Data| {
+        steps2.borrow_mut().push((step, data.clone()))
+    }));
+    (steps, subscription)
+}
+
+/// @returns The data of the interaction when it stopped, if it did.
+pub(crate) fn stopped<D: Clone>(steps: &Steps<D>) -> Option<D> {
+    steps
+        .borrow()
+        .iter()
+        .find(|(step, _)| *step == FsmStep::Stopped)
+        .map(|(_, data)| data.clone())
+}
+
+pub(crate) fn started<D>(steps: &Steps<D>) -> bool {
+    steps.borrow().iter().any(|(step, _)| *step == FsmStep::Started)
+}
+
+pub(crate) fn cancelled<D>(steps: &Steps<D>) -> bool {
+    steps.borrow().iter().any(|(step, _)| *step == FsmStep::Cancelled)
+}
+
+#[test]
+fn point_data_from_event() {
+    let modifiers = Modifiers {
+        ctrl: true,
+        ..Modifiers::NONE
+    };
+    let event = PointerEvent::new(1.0, 2.0, MouseButton::Middle)
+        .with_modifiers(modifiers)
+        .with_target(WidgetId(7));
+    assert_eq!(
+        PointData::from(&event),
+        PointData {
+            x: 1.0,
+            y: 2.0,
+            button: MouseButton::Middle,
+            modifiers,
+            target: Some(WidgetId(7)),
+        }
+    );
+}
+
+#[test]
+fn not_running_when_created() {
+    let press = Press::new();
+    assert!(!press.is_running());
+    assert_eq!(*press.get_data(), PointData::default());
+}
+
+#[test]
+fn cancel_interaction() {
+    let mut press = Press::new();
+    let (steps, _sub) = record(&press);
+    press.cancel();
+    assert!(!cancelled(&steps));
+    assert!(!press.is_running());
+}
+
+#[test]
+fn other_events_ignored() {
+    let mut press = Press::new();
+    assert!(!press.process_event(&UIEvent::FocusIn(WidgetId(1))));
+    assert!(!press.process_event(&up(1.0, 1.0)));
+}
+
+#[test]
+fn src_tgt_points_data() {
+    let data = SrcTgtPointsData {
+        src: PointData {
+            x: 1.0,
+            y: 5.0,
+            ..PointData::default()
+        },
+        tgt: PointData {
+            x: 4.0,
+            y: 2.0,
+            modifiers: Modifiers {
+                alt: true,
+                ..Modifiers::NONE
+            },
+            ..PointData::default()
+        },
+    };
+    assert_eq!(data.get_diff_x(), 3.0);
+    assert_eq!(data.get_diff_y(), -3.0);
+    assert!(data.get_modifiers().alt);
+}
@@ -0,0 +1,472 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{
+    linearhistory::LinearHistoryImpl,
+    messageformatter::MessageFormatter,
+    undoble::{Undoable, UndoableSnapshot},
+    undohistory::{UndoHistory, UndoHistoryBase, UndoableObserver},
+};
+use mockall::mock;
+
+mock! {
+    FakeUndoable {}
+    impl Undoable for FakeUndoable {
+        fn undo(&mut self);
+        fn redo(&mut self);
+        fn get_undo_name(&self) -> String;
+        fn visual_snapshot(&self) -> Option<UndoableSnapshot>;
+        fn memory_footprint(&self) -> usize;
+    }
+}
+
+fn undoable(name: &'static str) -> Box<MockFakeUndoable> {
+    let mut undoable = MockFakeUndoable::new();
+    undoable.expect_get_undo_name().return_const(name.to_string());
+    Box::new(undoable)
+}
+
+fn names(undoables: &[Box<dyn Undoable>]) -> Vec<String> {
+    undoables.iter().map(|u| u.get_undo_name()).collect()
+}
+
+#[test]
+fn size_max_default() {
+    assert_eq!(LinearHistoryImpl::new().get_size_max(), 100);
+}
+
+#[test]
+fn empty_after_creation() {
+    let history = LinearHistoryImpl::new();
+    assert!(history.get_undo().is_empty());
+    assert!(history.get_redo().is_empty());
+    assert!(history.get_last_undo().is_none());
+    assert!(history.get_last_redo().is_none());
+}
+
+#[test]
+fn undo_calls_undo() {
+    let mut history = LinearHistoryImpl::new();
+    let mut u = undoable("a");
+    u.expect_undo().times(1).return_const(());
+    u.expect_redo().never();
+    history.add(u);
+    history.undo();
+    assert!(history.get_last_undo().is_none());
+    assert_eq!(history.get_last_redo().unwrap().get_undo_name(), "a");
+}
+
+#[test]
+fn redo_calls_redo() {
+    let mut history = LinearHistoryImpl::new();
+    let mut u = undoable("a");
+    u.expect_undo().times(1).return_const(());
+    u.expect_redo().times(1).return_const(());
+    history.add(u);
+    history.undo();
+    history.redo();
+    assert_eq!(history.get_last_undo().unwrap().get_undo_name(), "a");
+    assert!(history.get_last_redo().is_none());
+}
+
+#[test]
+fn undo_when_empty_does_nothing() {
+    let mut history = LinearHistoryImpl::new();
+    history.undo();
+    assert!(history.get_undo().is_empty());
+    assert!(history.get_redo().is_empty());
+}
+
+#[test]
+fn redo_when_empty_does_nothing() {
+    let mut history = LinearHistoryImpl::new();
+    history.add(undoable("a"));
+    history.redo();
+    assert_eq!(names(history.get_undo()), vec!["a"]);
+    assert!(history.get_redo().is_empty());
+}
+
+#[test]
+fn add_with_zero_size_max() {
+    let mut history = LinearHistoryImpl::new();
+    history.set_size_max(0);
+    history.add(undoable("a"));
+    assert!(history.get_undo().is_empty());
+    assert!(history.get_last_undo().is_none());
+}
+
+#[test]
+fn add_with_limited_size_max() {
+    let mut history = LinearHistoryImpl::new();
+    history.set_size_max(2);
+    history.add(undoable("a"));
+    history.add(undoable("b"));
+    history.add(undoable("c"));
+    assert_eq!(names(history.get_undo()), vec!["b", "c"]);
+    assert_eq!(history.get_last_undo().unwrap().get_undo_name(), "c");
+}
+
+#[test]
+fn add_clears_redos() {
+    let mut history = LinearHistoryImpl::new();
+    let mut u = undoable("a");
+    u.expect_undo().return_const(());
+    history.add(u);
+    history.undo();
+    history.add(undoable("b"));
+    assert!(history.get_redo().is_empty());
+    assert!(history.get_last_redo().is_none());
+    assert_eq!(names(history.get_undo()), vec!["b"]);
+}
+
+#[test]
+fn undo_order() {
+    let mut history = LinearHistoryImpl::new();
+    let mut a = undoable("a");
+    let mut b = undoable("b");
+    a.expect_undo().times(1).return_const(());
+    b.expect_undo().times(1).return_const(());
+    history.add(a);
+    history.add(b);
+    history.undo();
+    assert_eq!(history.get_last_undo().unwrap().get_undo_name(), "a");
+    history.undo();
+    assert_eq!(names(history.get_redo()), vec!["b", "a"]);
+    assert_eq!(history.get_last_redo().unwrap().get_undo_name(), "a");
+}
+
+#[test]
+fn clear() {
+    let mut history = LinearHistoryImpl::new();
+    let mut b = undoable("b");
+    b.expect_undo().return_const(());
+    history.add(undoable("a"));
+    history.add(b);
+    history.undo();
+    history.clear();
+    assert!(history.get_undo().is_empty());
+    assert!(history.get_redo().is_empty());
+}
+
+#[test]
+fn set_size_max() {
+    let mut history = LinearHistoryImpl::new();
+    history.set_size_max(5);
+    assert_eq!(history.get_size_max(), 5);
+}
+
+#[test]
+fn set_size_max_removes_oldest_undos() {
+    let mut history = LinearHistoryImpl::new();
+    history.add(undoable("a"));
+    history.add(undoable("b"));
+    history.add(undoable("c"));
+    history.set_size_max(1);
+    assert_eq!(names(history.get_undo()), vec!["c"]);
+}
+
+#[test]
+fn set_size_max_keeps_redos() {
+    let mut history = LinearHistoryImpl::new();
+    let mut b = undoable("b");
+    b.expect_undo().return_const(());
+    history.add(undoable("a"));
+    history.add(b);
+    history.undo();
+    history.set_size_max(0);
+    assert!(history.get_undo().is_empty());
+    assert_eq!(names(history.get_redo()), vec!["b"]);
+}
+
+#[test]
+fn set_size_max_greater_keeps_undos() {
+    let mut history = LinearHistoryImpl::new();
+    history.add(undoable("a"));
+    history.add(undoable("b"));
+    history.set_size_max(10);
+    assert_eq!(names(history.get_undo()), vec!["a", "b"]);
+}
+
+type Notifications = Rc<RefCell<Vec<Option<String>>>>;
+
+fn recorder(notifications: &Notifications) -> Box<UndoableObserver> {
+    let notifications = notifications.clone();
+    Box::new(move |undoable| {
+        notifications
+            .borrow_mut()
+            .push(undoable.map(|u| u.get_undo_name()))
+    })
+}
+
+#[test]
+fn add_notifies_undos() {
+    let undos = Notifications::default();
+    let redos = Notifications::default();
+    let mut history = LinearHistoryImpl::new();
+    let _sub_undos = history.subscribe_undos(recorder(&undos));
+    let _sub_redos = history.subscribe_redos(recorder(&redos));
+    history.add(undoable("a"));
+    assert_eq!(*undos.borrow(), vec![Some("a".to_string())]);
+    assert!(redos.borrow().is_empty());
+}
+
+#[test]
+fn add_with_redos_notifies_redos() {
+    let redos = Notifications::default();
+    let mut history = LinearHistoryImpl::new();
+    let mut a = undoable("a");
+    a.expect_undo().return_const(());
+    history.add(a);
+    history.undo();
+    let _sub = history.subscribe_redos(recorder(&redos));
+    history.add(undoable("b"));
+    assert_eq!(*redos.borrow(), vec![None]);
+}
+
+#[test]
+fn undo_redo_notify() {
+    let undos = Notifications::default();
+    let redos = Notifications::default();
+    let mut history = LinearHistoryImpl::new();
+    let mut b = undoable("b");
+    b.expect_undo().return_const(());
+    b.expect_redo().return_const(());
+    history.add(undoable("a"));
+    history.add(b);
+    let _sub_undos = history.subscribe_undos(recorder(&undos));
+    let _sub_redos = history.subscribe_redos(recorder(&redos));
+    history.undo();
+    history.redo();
+    assert_eq!(
+        *undos.borrow(),
+        vec![Some("a".to_string()), Some("b".to_string())]
+    );
+    assert_eq!(*redos.borrow(), vec![Some("b".to_string()), None]);
+}
+
+#[test]
+fn undo_when_empty_does_not_notify() {
+    let undos = Notifications::default();
+    let mut history = LinearHistoryImpl::new();
+    let _sub = history.subscribe_undos(recorder(&undos));
+    history.undo();
+    history.redo();
+    assert!(undos.borrow().is_empty());
+}
+
+#[test]
+fn clear_notifies() {
+    let undos = Notifications::default();
+    let redos = Notifications::default();
+    let mut history = LinearHistoryImpl::new();
+    let mut b = undoable("b");
+    b.expect_undo().return_const(());
+    history.add(undoable("a"));
+    history.add(b);
+    history.undo();
+    let _sub_undos = history.subscribe_undos(recorder(&undos));
+    let _sub_redos = history.subscribe_redos(recorder(&redos));
+    history.clear();
+    history.clear();
+    assert_eq!(*undos.borrow(), vec![None]);
+    assert_eq!(*redos.borrow(), vec![None]);
+}
+
+#[test]
+fn set_size_max_notifies_when_undos_emptied() {
+    let undos = Notifications::default();
+    let mut history = LinearHistoryImpl::new();
+    history.add(undoable("a"));
+    history.add(undoable("b"));
+    let _sub = history.subscribe_undos(recorder(&undos));
+    history.set_size_max(1);
+    assert!(undos.borrow().is_empty());
+    history.set_size_max(0);
+    assert_eq!(*undos.borrow(), vec![None]);
+}
+
+#[test]
+fn dropped_subscription_not_notified() {
+    let undos = Notifications::default();
+    let mut history = LinearHistoryImpl::new();
+    let sub = history.subscribe_undos(recorder(&undos));
+    drop(sub);
+    history.add(undoable("a"));
+    assert!(undos.borrow().is_empty());
+}
+
+struct PrefixFormatter;
+
+impl MessageFormatter for PrefixFormatter {
+    fn format_undo(&self, name: &str) -> String {
+        format!("Annuler {}", name)
+    }
+
+    fn format_redo(&self, name: &str) -> String {
+        format!("Refaire {}", name)
+    }
+}
+
+#[test]
+fn messages_when_empty() {
+    let history = LinearHistoryImpl::new();
+    assert!(history.get_last_undo_message().is_none());
+    assert!(history.get_last_redo_message().is_none());
+    assert_eq!(history.get_last_or_empty_undo_message(), "");
+    assert_eq!(history.get_last_or_empty_redo_message(), "");
+}
+
+#[test]
+fn undo_message_default_formatter() {
+    let mut history = LinearHistoryImpl::new();
+    history.add(undoable("Move 3 shapes"));
+    assert_eq!(
+        history.get_last_undo_message().as_deref(),
+        Some("Move 3 shapes")
+    );
+    assert_eq!(history.get_last_or_empty_undo_message(), "Move 3 shapes");
+}
+
+#[test]
+fn redo_message_default_formatter() {
+    let mut history = LinearHistoryImpl::new();
+    let mut a = undoable("Move 3 shapes");
+    a.expect_undo().return_const(());
+    history.add(a);
+    history.undo();
+    assert_eq!(
+        history.get_last_redo_message().as_deref(),
+        Some("Move 3 shapes")
+    );
+    assert_eq!(history.get_last_or_empty_redo_message(), "Move 3 shapes");
+    assert_eq!(history.get_last_or_empty_undo_message(), "");
+}
+
+#[test]
+fn messages_custom_formatter() {
+    let mut history = LinearHistoryImpl::new();
+    history.set_message_formatter(Box::new(PrefixFormatter));
+    let mut b = undoable("b");
+    b.expect_undo().return_const(());
+    history.add(undoable("a"));
+    history.add(b);
+    history.undo();
+    assert_eq!(history.get_last_undo_message().as_deref(), Some("Annuler a"));
+    assert_eq!(history.get_last_redo_message().as_deref(), Some("Refaire b"));
+}
+
+fn undoable_with_snapshot(name: &'static str, snapshot: Option<UndoableSnapshot>) -> Box<MockFakeUndoable> {
+    let mut undoable = undoable(name);
+    undoable.expect_visual_snapshot().return_const(snapshot);
+    undoable
+}
+
+#[test]
+fn snapshots_when_empty() {
+    let history = LinearHistoryImpl::new();
+    assert!(history.get_last_undo_snapshot().is_none());
+    assert!(history.get_last_redo_snapshot().is_none());
+    assert!(history.get_undo_snapshots().is_empty());
+}
+
+#[test]
+fn undo_snapshots() {
+    let mut history = LinearHistoryImpl::new();
+    let svg = UndoableSnapshot::Svg("<svg/>".to_string());
+    history.add(undoable_with_snapshot("a", None));
+    history.add(undoable_with_snapshot("b", Some(svg.clone())));
+    assert_eq!(history.get_undo_snapshots(), vec![None, Some(svg.clone())]);
+    assert_eq!(history.get_last_undo_snapshot(), Some(svg));
+}
+
+#[test]
+fn redo_snapshots() {
+    let mut history = LinearHistoryImpl::new();
+    let thumbnail = UndoableSnapshot::Rgba {
+        width: 1,
+        height: 1,
+        pixels: vec![255, 0, 0, 255],
+    };
+    let mut a = undoable_with_snapshot("a", Some(thumbnail.clone()));
+    a.expect_undo().return_const(());
+    history.add(a);
+    history.undo();
+    assert_eq!(history.get_redo_snapshots(), vec![Some(thumbnail.clone())]);
+    assert_eq!(history.get_last_redo_snapshot(), Some(thumbnail));
+    assert!(history.get_last_undo_snapshot().is_none());
+}
+
+fn undoable_with_footprint(name: &'static str, footprint: usize) -> Box<MockFakeUndoable> {
+    let mut undoable = undoable(name);
+    undoable.expect_memory_footprint().return_const(footprint);
+    undoable
+}
+
+#[test]
+fn memory_max_default() {
+    assert!(LinearHistoryImpl::new().get_memory_max().is_none());
+}
+
+#[test]
+fn memory_footprint() {
+    let mut history = LinearHistoryImpl::new();
+    history.add(undoable_with_footprint("a", 10));
+    history.add(undoable_with_footprint("b", 20));
+    assert_eq!(history.get_memory_footprint(), 30);
+}
+
+#[test]
+fn add_evicts_oldest_over_memory_max() {
+    let mut history = LinearHistoryImpl::new();
+    history.set_memory_max(Some(100));
+    history.add(undoable_with_footprint("a", 40));
+    history.add(undoable_with_footprint("b", 40));
+    history.add(undoable_with_footprint("c", 40));
+    assert_eq!(names(history.get_undo()), vec!["b", "c"]);
+    history.add(undoable_with_footprint("d", 90));
+    assert_eq!(names(history.get_undo()), vec!["d"]);
+}
+
+#[test]
+fn last_undoable_kept_over_memory_max() {
+    let mut history = LinearHistoryImpl::new();
+    history.set_memory_max(Some(10));
+    history.add(undoable_with_footprint("a", 50));
+    assert_eq!(names(history.get_undo()), vec!["a"]);
+}
+
+#[test]
+fn set_memory_max_evicts() {
+    let mut history = LinearHistoryImpl::new();
+    history.add(undoable_with_footprint("a", 40));
+    history.add(undoable_with_footprint("b", 40));
+    history.add(undoable_with_footprint("c", 40));
+    history.set_memory_max(Some(80));
+    assert_eq!(names(history.get_undo()), vec!["b", "c"]);
+    assert_eq!(history.get_memory_max(), Some(80));
+}
+
+#[test]
+fn memory_max_with_size_max() {
+    let mut history = LinearHistoryImpl::new();
+    history.set_size_max(2);
+    history.set_memory_max(Some(1000));
+    history.add(undoable_with_footprint("a", 1));
+    history.add(undoable_with_footprint("b", 1));
+    history.add(undoable_with_footprint("c", 1));
+    assert_eq!(names(history.get_undo()), vec!["b", "c"]);
+    assert_eq!(history.get_size_max(), 2);
+}
@@ -0,0 +1,57 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::time::Duration;
+
+use crate::{clock::VirtualClock, interaction::Interaction, longmousedown::LongMouseDown};
+
+use super::interaction::{cancelled, down, mv, record, stopped, up};
+
+fn long_mouse_down(clock: &VirtualClock) -> LongMouseDown {
+    let mut interaction = LongMouseDown::new(Duration::from_secs(1));
+    interaction.set_clock(Box::new(clock.clone()));
+    interaction
+}
+
+#[test]
+fn duration() {
+    assert_eq!(LongMouseDown::new(Duration::from_secs(2)).get_duration(), Duration::from_secs(2));
+}
+
+#[test]
+fn long_press_stops() {
+    let clock = VirtualClock::new();
+    let mut interaction = long_mouse_down(&clock);
+    let (steps, _sub) = record(&interaction);
+    interaction.process_event(&down(1.0, 2.0));
+    interaction.process_event(&mv(1.0, 3.0));
+    clock.advance(Duration::from_secs(1));
+    assert!(interaction.check_timeout());
+    let data = stopped(&steps).unwrap();
+    assert_eq!((data.x, data.y), (1.0, 2.0));
+}
+
+#[test]
+fn early_release_cancels() {
+    let clock = VirtualClock::new();
+    let mut interaction = long_mouse_down(&clock);
+    let (steps, _sub) = record(&interaction);
+    interaction.process_event(&down(1.0, 2.0));
+    clock.advance(Duration::from_millis(500));
+    interaction.process_event(&up(1.0, 2.0));
+    assert!(cancelled(&steps));
+    clock.advance(Duration::from_secs(1));
+    assert!(!interaction.check_timeout());
+    assert!(stopped(&steps).is_none());
+}
@@ -0,0 +1,211 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::{cell::RefCell, error::Error, rc::Rc};
+
+use crate::{
+    command::{CmdOutcome, CmdStatus, Command, CommandBase, CustomCmd},
+    historyhandle::HistoryHandle,
+    linearhistory::LinearHistoryImpl,
+    macrocmd::MacroCmd,
+    undoble::Undoable,
+};
+
+type Log = Rc<RefCell<Vec<String>>>;
+
+struct StubCmd {
+    name: &'static str,
+    fail: bool,
+    can_do: bool,
+    log: Log,
+}
+
+fn stub(name: &'static str, log: &Log) -> StubCmd {
+    StubCmd {
+        name,
+        fail: false,
+        can_do: true,
+        log: log.clone(),
+    }
+}
+
+impl CommandBase for StubCmd {
+    fn create_memento(&mut self) {
+        self.log.borrow_mut().push(format!("memento {}", self.name));
+    }
+
+    fn can_execute(&self) -> bool {
+        self.can_do
+    }
+}
+
+impl CustomCmd for StubCmd {
+    type Error = String;
+
+    fn execution(&mut self) -> Result<(), Self::Error> {
+        if self.fail {
+            Err(format!("{} failed", self.name))
+        } else {
+            self.log.borrow_mut().push(format!("exec {}", self.name));
+            Ok(())
+        }
+    }
+}
+
+impl Undoable for StubCmd {
+    fn undo(&mut self) {
+        self.log.borrow_mut().push(format!("undo {}", self.name));
+    }
+
+    fn redo(&mut self) {
+        self.log.borrow_mut().push(format!("redo {}", self.name));
+    }
+
+    fn get_undo_name(&self) -> String {
+        self.name.to_string()
+    }
+}
+
+#[test]
+fn cannot_execute_when_empty() {
+    assert!(!MacroCmd::new().as_command().can_execute());
+}
+
+#[test]
+fn cannot_execute_when_one_child_cannot() {
+    let log = Log::default();
+    let mut resize = stub("resize", &log);
+    resize.can_do = false;
+    let cmd = MacroCmd::new().with(stub("align", &log)).with(resize);
+    assert!(!cmd.as_command().can_execute());
+}
+
+#[test]
+fn executes_children_in_order() {
+    let log = Log::default();
+    let mut cmd = MacroCmd::new()
+        .with(stub("align", &log))
+        .with(stub("resize", &log))
+        .as_command();
+    assert!(cmd.execute().is_executed());
+    assert_eq!(
+        *log.borrow(),
+        vec!["memento align", "exec align", "memento resize", "exec resize"]
+    );
+}
+
+#[test]
+fn mementos_created_once() {
+    let log = Log::default();
+    let mut cmd = MacroCmd::new().with(stub("align", &log)).as_command();
+    cmd.execute();
+    cmd.execute();
+    assert_eq!(
+        *log.borrow(),
+        vec!["memento align", "exec align", "exec align"]
+    );
+}
+
+#[test]
+fn failure_rolls_back_executed_children() {
+    let log = Log::default();
+    let mut failing = stub("c", &log);
+    failing.fail = true;
+    let mut cmd = MacroCmd::new()
+        .with(stub("a", &log))
+        .with(stub("b", &log))
+        .with(failing)
+        .with(stub("d", &log))
+        .as_command();
+    match cmd.execute() {
+        CmdOutcome::Failed(error) => {
+            assert_eq!(error.index, 2);
+            assert_eq!(error.source.to_string(), "c failed");
+            assert_eq!(
+                error.to_string(),
+                "command 2 of the macro command failed: c failed"
+            );
+            assert!(error.source().is_some());
+        }
+        _ => panic!("the macro command should fail"),
+    }
+    assert_eq!(cmd.get_status(), CmdStatus::Executed);
+    assert_eq!(
+        *log.borrow(),
+        vec![
+            "memento a",
+            "exec a",
+            "memento b",
+            "exec b",
+            "memento c",
+            "undo b",
+            "undo a"
+        ]
+    );
+}
+
+#[test]
+fn undo_in_reverse_order_and_redo_in_order() {
+    let log = Log::default();
+    let mut cmd = MacroCmd::new().with(stub("a", &log)).with(stub("b", &log));
+    cmd.undo();
+    cmd.redo();
+    assert_eq!(*log.borrow(), vec!["undo b", "undo a", "redo a", "redo b"]);
+}
+
+#[test]
+fn combined_undo_name() {
+    let log = Log::default();
+    let cmd = MacroCmd::new().with(stub("align", &log)).with(stub("resize", &log));
+    assert_eq!(cmd.get_undo_name(), "align, resize");
+}
+
+#[test]
+fn custom_undo_name() {
+    let log = Log::default();
+    let cmd = MacroCmd::new()
+        .with_name("Align and resize")
+        .with(stub("align", &log))
+        .with(stub("resize", &log));
+    assert_eq!(cmd.get_undo_name(), "Align and resize");
+}
+
+#[test]
+fn add_children() {
+    let log = Log::default();
+    let mut cmd = MacroCmd::new();
+    assert!(cmd.is_empty());
+    cmd.add(stub("a", &log));
+    cmd.add(MacroCmd::new().with(stub("b", &log)));
+    assert_eq!(cmd.len(), 2);
+    assert_eq!(cmd.get_undo_name(), "a, b");
+}
+
+#[test]
+fn undone_as_one_step_in_history() {
+    let log = Log::default();
+    let history = HistoryHandle::new(LinearHistoryImpl::new());
+    let mut cmd: Command<MacroCmd> = MacroCmd::new()
+        .with(stub("a", &log))
+        .with(stub("b", &log))
+        .as_command();
+    cmd.execute();
+    cmd.set_history(history.clone());
+    cmd.done();
+    assert!(cmd.child.is_in_history());
+    log.borrow_mut().clear();
+    history.undo();
+    assert!(!history.can_undo());
+    assert_eq!(*log.borrow(), vec!["undo b", "undo a"]);
+}
@@ -0,0 +1,186 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{
+    command::{CmdOutcome, Command},
+    historyhandle::HistoryHandle,
+    linearhistory::LinearHistoryImpl,
+    memento::{Memento, ModelChange, ModelCmd},
+    undoble::Undoable,
+    undohistory::UndoHistoryBase,
+};
+
+#[derive(Clone, PartialEq, Debug, Default)]
+struct Shape {
+    x: i32,
+    color: String,
+}
+
+struct MoveShape(i32);
+
+impl ModelChange for MoveShape {
+    type Model = Shape;
+    type Error = String;
+
+    fn can_apply(&self, _model: &Shape) -> bool {
+        self.0 != 0
+    }
+
+    fn apply(&mut self, model: &mut Shape) -> Result<(), String> {
+        if self.0 > 100 {
+            return Err("too far".to_string());
+        }
+        model.x += self.0;
+        Ok(())
+    }
+
+    fn get_undo_name(&self) -> String {
+        "Move".to_string()
+    }
+
+    fn model_footprint(&self, model: &Shape) -> usize {
+        std::mem::size_of_val(model) + model.color.capacity()
+    }
+}
+
+fn shape() -> Rc<RefCell<Shape>> {
+    Rc::new(RefCell::new(Shape {
+        x: 1,
+        color: "red".to_string(),
+    }))
+}
+
+#[test]
+fn memento_not_captured_after_creation() {
+    let memento = Memento::<i32>::new();
+    assert!(!memento.is_captured());
+    assert!(memento.get_state().is_none());
+}
+
+#[test]
+fn memento_keeps_first_capture() {
+    let mut memento = Memento::new();
+    assert!(memento.capture(&1));
+    assert!(!memento.capture(&2));
+    assert_eq!(memento.get_state(), Some(&1));
+}
+
+#[test]
+fn memento_restore_and_reapply() {
+    let mut memento = Memento::new();
+    let mut color = "red".to_string();
+    memento.capture(&color);
+    color = "blue".to_string();
+    memento.restore(&mut color);
+    assert_eq!(color, "red");
+    memento.reapply(&mut color);
+    assert_eq!(color, "blue");
+}
+
+#[test]
+fn memento_restore_without_capture() {
+    let mut memento = Memento::new();
+    let mut x = 2;
+    memento.restore(&mut x);
+    memento.reapply(&mut x);
+    assert_eq!(x, 2);
+}
+
+#[test]
+fn model_cmd_execute() {
+    let model = shape();
+    let mut cmd = Command::new(ModelCmd::new(model.clone(), MoveShape(10)));
+    assert!(cmd.execute().is_executed());
+    assert_eq!(model.borrow().x, 11);
+    assert_eq!(cmd.child.get_memento().get_state().unwrap().x, 1);
+}
+
+#[test]
+fn model_cmd_cannot_execute() {
+    let model = shape();
+    let mut cmd = Command::new(ModelCmd::new(model.clone(), MoveShape(0)));
+    assert!(matches!(cmd.execute(), CmdOutcome::NotExecutable));
+    assert!(!cmd.child.get_memento().is_captured());
+}
+
+#[test]
+fn model_cmd_failure() {
+    let model = shape();
+    let mut cmd = Command::new(ModelCmd::new(model.clone(), MoveShape(200)));
+    assert!(matches!(cmd.execute(), CmdOutcome::Failed(error) if error == "too far"));
+    assert_eq!(model.borrow().x, 1);
+}
+
+#[test]
+fn model_cmd_undo_redo_through_history() {
+    let model = shape();
+    let history = HistoryHandle::new(LinearHistoryImpl::new());
+    let mut cmd = Command::new(ModelCmd::new(model.clone(), MoveShape(10)));
+    cmd.execute();
+    cmd.set_history(history.clone());
+    cmd.done();
+    assert!(cmd.child.is_in_history());
+    model.borrow_mut().color = "blue".to_string();
+    history.undo();
+    assert_eq!(
+        *model.borrow(),
+        Shape {
+            x: 1,
+            color: "red".to_string()
+        }
+    );
+    history.redo();
+    assert_eq!(
+        *model.borrow(),
+        Shape {
+            x: 11,
+            color: "blue".to_string()
+        }
+    );
+    assert_eq!(history.borrow().get_last_undo_message(), Some("Move".to_string()));
+}
+
+#[test]
+fn model_cmd_executed_twice_keeps_first_state() {
+    let model = shape();
+    let mut cmd = Command::new(ModelCmd::new(model.clone(), MoveShape(10)));
+    cmd.execute();
+    cmd.execute();
+    assert_eq!(model.borrow().x, 21);
+    assert_eq!(cmd.child.get_memento().get_state().unwrap().x, 1);
+}
+
+#[test]
+fn memento_footprint_counts_captured_states() {
+    let mut memento = Memento::new();
+    assert_eq!(memento.memory_footprint(|_: &i32| 4), 0);
+    memento.capture(&1);
+    assert_eq!(memento.memory_footprint(|_| 4), 4);
+    let mut state = 2;
+    memento.restore(&mut state);
+    assert_eq!(memento.memory_footprint(|_| 4), 8);
+}
+
+#[test]
+fn model_cmd_footprint_includes_captured_model() {
+    let shape = shape();
+    let mut cmd = Command::new(ModelCmd::new(shape.clone(), MoveShape(10)));
+    let empty = cmd.child.memory_footprint();
+    assert_eq!(empty, std::mem::size_of::<ModelCmd<MoveShape>>());
+    cmd.execute();
+    let model = std::mem::size_of::<Shape>() + shape.borrow().color.capacity();
+    assert_eq!(cmd.child.memory_footprint(), empty + model);
+}
@@ -0,0 +1,248 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::{any::Any, cell::RefCell, rc::Rc, time::Duration};
+
+use crate::{
+    clock::VirtualClock,
+    linearhistory::LinearHistoryImpl,
+    mergepolicy::MergePolicy,
+    treehistory::TreeUndoHistory,
+    undoble::Undoable,
+    undohistory::{UndoHistory, UndoHistoryBase},
+};
+
+/// Types text in a shared document.
+struct TypeText {
+    text: String,
+    doc: Rc<RefCell<String>>,
+}
+
+impl Undoable for TypeText {
+    fn undo(&mut self) {
+        let mut doc = self.doc.borrow_mut();
+        let len = doc.len() - self.text.len();
+        doc.truncate(len);
+    }
+
+    fn redo(&mut self) {
+        self.doc.borrow_mut().push_str(&self.text);
+    }
+
+    fn get_undo_name(&self) -> String {
+        "Typing".to_string()
+    }
+
+    fn merge_with(&mut self, next: &dyn Undoable) -> bool {
+        match next.as_any().and_then(|next| next.downcast_ref::<TypeText>()) {
+            Some(next) => {
+                self.text.push_str(&next.text);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn as_any(&self) -> Option<&dyn Any> {
+        Some(self)
+    }
+}
+
+/// An undoable that does not support merging.
+struct Rename;
+
+impl Undoable for Rename {
+    fn undo(&mut self) {}
+
+    fn redo(&mut self) {}
+
+    fn get_undo_name(&self) -> String {
+        "Rename".to_string()
+    }
+}
+
+fn type_text(text: &str, doc: &Rc<RefCell<String>>) -> Box<TypeText> {
+    doc.borrow_mut().push_str(text);
+    Box::new(TypeText {
+        text: text.to_string(),
+        doc: doc.clone(),
+    })
+}
+
+#[test]
+fn default_policy() {
+    assert_eq!(LinearHistoryImpl::new().get_merge_policy(), MergePolicy::Never);
+    assert_eq!(TreeUndoHistory::new().get_merge_policy(), MergePolicy::Never);
+}
+
+#[test]
+fn merge_consecutive_undoables() {
+    let doc = Rc::default();
+    let mut history = LinearHistoryImpl::new();
+    history.set_merge_policy(MergePolicy::Always);
+    history.add(type_text("a", &doc));
+    history.add(type_text("b", &doc));
+    history.add(type_text("c", &doc));
+    assert_eq!(history.get_undo().len(), 1);
+    history.undo();
+    assert_eq!(*doc.borrow(), "");
+    history.redo();
+    assert_eq!(*doc.borrow(), "abc");
+}
+
+#[test]
+fn no_merge_by_default() {
+    let doc = Rc::default();
+    let mut history = LinearHistoryImpl::new();
+    history.add(type_text("a", &doc));
+    history.add(type_text("b", &doc));
+    assert_eq!(history.get_undo().len(), 2);
+    history.undo();
+    assert_eq!(*doc.borrow(), "a");
+}
+
+#[test]
+fn no_merge_when_undoable_refuses() {
+    let doc = Rc::default();
+    let mut history = LinearHistoryImpl::new();
+    history.set_merge_policy(MergePolicy::Always);
+    history.add(Box::new(Rename));
+    history.add(type_text("a", &doc));
+    history.add(Box::new(Rename));
+    assert_eq!(history.get_undo().len(), 3);
+}
+
+#[test]
+fn no_merge_after_undo() {
+    let doc = Rc::default();
+    let mut history = LinearHistoryImpl::new();
+    history.set_merge_policy(MergePolicy::Always);
+    history.add(type_text("a", &doc));
+    history.add(type_text("b", &doc));
+    history.undo();
+    history.redo();
+    history.add(type_text("c", &doc));
+    assert_eq!(history.get_undo().len(), 2);
+    history.undo();
+    assert_eq!(*doc.borrow(), "ab");
+}
+
+#[test]
+fn merge_with_same_name_policy() {
+    let doc = Rc::default();
+    let mut history = LinearHistoryImpl::new();
+    history.set_merge_policy(MergePolicy::SameName);
+    history.add(type_text("a", &doc));
+    history.add(type_text("b", &doc));
+    history.add(Box::new(Rename));
+    history.add(type_text("c", &doc));
+    assert_eq!(history.get_undo().len(), 3);
+}
+
+#[test]
+fn merge_within_delay() {
+    let doc = Rc::default();
+    let clock = VirtualClock::new();
+    let mut history = LinearHistoryImpl::new();
+    history.set_clock(Box::new(clock.clone()));
+    history.set_merge_policy(MergePolicy::Within(Duration::from_millis(500)));
+    history.add(type_text("a", &doc));
+    clock.advance(Duration::from_millis(499));
+    history.add(type_text("b", &doc));
+    clock.advance(Duration::from_millis(499));
+    history.add(type_text("c", &doc));
+    assert_eq!(history.get_undo().len(), 1);
+}
+
+#[test]
+fn no_merge_after_delay() {
+    let doc = Rc::default();
+    let clock = VirtualClock::new();
+    let mut history = LinearHistoryImpl::new();
+    history.set_clock(Box::new(clock.clone()));
+    history.set_merge_policy(MergePolicy::Within(Duration::from_millis(500)));
+    history.add(type_text("a", &doc));
+    clock.advance(Duration::from_millis(500));
+    history.add(type_text("b", &doc));
+    assert_eq!(history.get_undo().len(), 2);
+}
+
+#[test]
+fn tree_no_merge_after_delay() {
+    let doc = Rc::default();
+    let clock = VirtualClock::new();
+    let mut history = TreeUndoHistory::new();
+    history.set_clock(Box::new(clock.clone()));
+    history.set_merge_policy(MergePolicy::Within(Duration::from_millis(500)));
+    history.add(type_text("a", &doc));
+    clock.advance(Duration::from_millis(100));
+    history.add(type_text("b", &doc));
+    clock.advance(Duration::from_secs(1));
+    history.add(type_text("c", &doc));
+    assert_eq!(history.get_nodes().len(), 2);
+}
+
+#[test]
+fn merge_clears_redos() {
+    let doc = Rc::default();
+    let mut history = LinearHistoryImpl::new();
+    history.set_merge_policy(MergePolicy::Never);
+    history.add(type_text("a", &doc));
+    history.add(type_text("b", &doc));
+    history.undo();
+    history.set_merge_policy(MergePolicy::Always);
+    history.add(type_text("c", &doc));
+    history.add(type_text("d", &doc));
+    assert!(history.get_redo().is_empty());
+    assert_eq!(history.get_undo().len(), 2);
+}
+
+#[test]
+fn merged_undoables_fill_size_max_once() {
+    let doc = Rc::default();
+    let mut history = LinearHistoryImpl::new();
+    history.set_size_max(1);
+    history.add(Box::new(Rename));
+    history.add(type_text("a", &doc));
+    history.add(type_text("b", &doc));
+    assert_eq!(history.get_undo().len(), 1);
+    assert_eq!(history.get_last_undo().unwrap().get_undo_name(), "Typing");
+}
+
+#[test]
+fn tree_merges_into_leaf() {
+    let doc = Rc::default();
+    let mut history = TreeUndoHistory::new();
+    history.set_merge_policy(MergePolicy::Always);
+    history.add(type_text("a", &doc));
+    history.add(type_text("b", &doc));
+    assert_eq!(history.get_nodes().len(), 1);
+    history.undo();
+    assert_eq!(*doc.borrow(), "");
+}
+
+#[test]
+fn tree_does_not_merge_into_node_with_branches() {
+    let doc = Rc::default();
+    let mut history = TreeUndoHistory::new();
+    history.set_merge_policy(MergePolicy::Never);
+    history.add(type_text("a", &doc));
+    history.add(type_text("b", &doc));
+    history.undo();
+    history.set_merge_policy(MergePolicy::Always);
+    history.add(type_text("c", &doc));
+    history.add(type_text("d", &doc));
+    assert_eq!(history.get_nodes().len(), 3);
+    assert_eq!(history.get_node(0).unwrap().get_children(), &[1, 2]);
+}
@@ -12,5 +12,28 @@
  * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
  */
 mod command;
+mod asynccmd;
 mod anoncmd;
+mod macrocmd;
+mod memento;
 mod undo;
+mod linearhistory;
+mod treehistory;
+mod historyhandle;
+mod subject;
+mod registry;
+mod event;
+mod clock;
+mod fsm;
+mod interaction;
+mod press;
+mod click;
+mod doubleclick;
+mod longmousedown;
+mod clicks;
+mod dnd;
+mod draglock;
+mod mergepolicy;
+mod historygroup;
+#[cfg(feature = "serde")]
+mod persistence;
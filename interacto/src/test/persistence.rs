@@ -0,0 +1,370 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::any::Any;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    command::{CommandBase, CustomCmd},
+    historygroup::UndoableGroup,
+    linearhistory::LinearHistoryImpl,
+    macrocmd::MacroCmd,
+    persistence::{LoadReport, UndoableTypeRegistry},
+    undoble::Undoable,
+    undohistory::{UndoHistory, UndoHistoryBase},
+};
+
+#[derive(Serialize, Deserialize)]
+struct Rename {
+    from: String,
+    to: String,
+}
+
+impl Undoable for Rename {
+    fn undo(&mut self) {}
+
+    fn redo(&mut self) {}
+
+    fn get_undo_name(&self) -> String {
+        format!("Rename {} to {}", self.from, self.to)
+    }
+
+    fn as_any(&self) -> Option<&dyn Any> {
+        Some(self)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Move {
+    dx: i32,
+    dy: i32,
+}
+
+impl Undoable for Move {
+    fn undo(&mut self) {}
+
+    fn redo(&mut self) {}
+
+    fn get_undo_name(&self) -> String {
+        format!("Move {} {}", self.dx, self.dy)
+    }
+
+    fn as_any(&self) -> Option<&dyn Any> {
+        Some(self)
+    }
+}
+
+/// An undoable that is not registered.
+struct Transient;
+
+impl Undoable for Transient {
+    fn undo(&mut self) {}
+
+    fn redo(&mut self) {}
+
+    fn get_undo_name(&self) -> String {
+        "Transient".to_string()
+    }
+}
+
+fn rename(from: &str, to: &str) -> Box<Rename> {
+    Box::new(Rename {
+        from: from.to_string(),
+        to: to.to_string(),
+    })
+}
+
+fn registry() -> UndoableTypeRegistry {
+    let mut registry = UndoableTypeRegistry::new();
+    registry.register::<Rename>("rename").register::<Move>("move");
+    registry
+}
+
+fn names(undoables: &[Box<dyn Undoable>]) -> Vec<String> {
+    undoables.iter().map(|u| u.get_undo_name()).collect()
+}
+
+fn history() -> LinearHistoryImpl {
+    let mut history = LinearHistoryImpl::new();
+    history.set_size_max(10);
+    history.add(rename("a", "b"));
+    history.add(Box::new(Move { dx: 1, dy: 2 }));
+    history.add(rename("b", "c"));
+    history.add(Box::new(Move { dx: 3, dy: 4 }));
+    history.undo();
+    history
+}
+
+fn assert_restored(history: &LinearHistoryImpl) {
+    assert_eq!(history.get_size_max(), 10);
+    assert_eq!(names(history.get_undo()), vec!["Rename a to b", "Move 1 2", "Rename b to c"]);
+    assert_eq!(names(history.get_redo()), vec!["Move 3 4"]);
+}
+
+#[test]
+fn registered_types() {
+    let registry = registry();
+    assert!(registry.is_registered("rename"));
+    assert!(registry.is_registered("move"));
+    assert!(!registry.is_registered("transient"));
+}
+
+#[test]
+fn json_round_trip() {
+    let registry = registry();
+    let json = registry.to_json(&history()).unwrap();
+    let mut restored = LinearHistoryImpl::new();
+    let report = registry.from_json(&mut restored, &json).unwrap();
+    assert_eq!(report, LoadReport::default());
+    assert_restored(&restored);
+}
+
+#[test]
+fn json_format() {
+    let mut history = LinearHistoryImpl::new();
+    history.add(Box::new(Move { dx: 1, dy: 2 }));
+    assert_eq!(
+        registry().to_json(&history).unwrap(),
+        r#"{"size_max":100,"undos":[{"type":"move","data":{"dx":1,"dy":2}}],"redos":[],"memory_max":null}"#
+    );
+}
+
+#[test]
+fn binary_round_trip() {
+    let registry = registry();
+    let bytes = registry.to_binary(&history()).unwrap();
+    let mut restored = LinearHistoryImpl::new();
+    let report = registry.from_binary(&mut restored, &bytes).unwrap();
+    assert_eq!(report, LoadReport::default());
+    assert_restored(&restored);
+}
+
+#[test]
+fn binary_is_more_compact() {
+    let registry = registry();
+    let history = history();
+    assert!(registry.to_binary(&history).unwrap().len() < registry.to_json(&history).unwrap().len());
+}
+
+#[test]
+fn load_replaces_content() {
+    let registry = registry();
+    let json = registry.to_json(&history()).unwrap();
+    let mut restored = LinearHistoryImpl::new();
+    restored.add(rename("x", "y"));
+    registry.from_json(&mut restored, &json).unwrap();
+    assert_restored(&restored);
+}
+
+#[test]
+fn save_unregistered_type_fails() {
+    let mut history = LinearHistoryImpl::new();
+    history.add(Box::new(Transient));
+    let error = registry().to_json(&history).unwrap_err();
+    assert!(error.to_string().contains("Transient"));
+}
+
+#[test]
+fn unknown_undo_drops_older_undos() {
+    let json = registry().to_json(&history()).unwrap();
+    let mut partial = UndoableTypeRegistry::new();
+    partial.register::<Rename>("rename");
+    let mut restored = LinearHistoryImpl::new();
+    let report = partial.from_json(&mut restored, &json).unwrap();
+    assert_eq!(names(restored.get_undo()), vec!["Rename b to c"]);
+    assert!(restored.get_redo().is_empty());
+    assert_eq!(
+        report,
+        LoadReport {
+            unknown_types: vec!["move".to_string(), "move".to_string()],
+            dropped: 3,
+        }
+    );
+}
+
+#[test]
+fn unknown_redo_drops_newer_redos() {
+    let mut history = LinearHistoryImpl::new();
+    history.add(rename("a", "b"));
+    history.add(Box::new(Move { dx: 1, dy: 2 }));
+    history.add(rename("b", "c"));
+    history.undo();
+    history.undo();
+    history.undo();
+    let bytes = registry().to_binary(&history).unwrap();
+    let mut partial = UndoableTypeRegistry::new();
+    partial.register::<Rename>("rename");
+    let mut restored = LinearHistoryImpl::new();
+    let report = partial.from_binary(&mut restored, &bytes).unwrap();
+    assert_eq!(names(restored.get_redo()), vec!["Rename a to b"]);
+    assert_eq!(report.dropped, 2);
+}
+
+#[test]
+fn invalid_json_fails() {
+    let mut restored = LinearHistoryImpl::new();
+    restored.add(rename("x", "y"));
+    assert!(registry().from_json(&mut restored, r#"{"size_max":1}"#).is_err());
+    assert_eq!(names(restored.get_undo()), vec!["Rename x to y"]);
+}
+
+#[test]
+fn load_applies_size_max() {
+    let registry = registry();
+    let json = registry.to_json(&history()).unwrap();
+    let mut restored = LinearHistoryImpl::new();
+    restored.set_size_max(2);
+    registry.from_json(&mut restored, &json).unwrap();
+    assert_eq!(restored.get_size_max(), 10);
+    assert_eq!(restored.get_undo().len(), 3);
+}
+
+/// A registered undoable command, to put in macro commands.
+#[derive(Serialize, Deserialize)]
+struct Resize {
+    factor: u32,
+}
+
+impl CommandBase for Resize {}
+
+impl CustomCmd for Resize {
+    type Error = String;
+
+    fn execution(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+impl Undoable for Resize {
+    fn undo(&mut self) {}
+
+    fn redo(&mut self) {}
+
+    fn get_undo_name(&self) -> String {
+        format!("Resize {}", self.factor)
+    }
+
+    fn as_any(&self) -> Option<&dyn Any> {
+        Some(self)
+    }
+}
+
+fn composite_history() -> LinearHistoryImpl {
+    let mut history = LinearHistoryImpl::new();
+    history.add(Box::new(
+        MacroCmd::new()
+            .with(Resize { factor: 2 })
+            .with(Resize { factor: 3 }),
+    ));
+    history.begin_group("Edit");
+    history.add(rename("a", "b"));
+    history.add(Box::new(MacroCmd::new().with_name("Grow").with(Resize { factor: 4 })));
+    history.end_group();
+    history
+}
+
+fn composite_registry() -> UndoableTypeRegistry {
+    let mut registry = registry();
+    registry.register::<Resize>("resize");
+    registry
+}
+
+fn assert_composites_restored(history: &LinearHistoryImpl) {
+    assert_eq!(names(history.get_undo()), vec!["Resize 2, Resize 3", "Edit"]);
+    let first = history.get_undo()[0].as_any().unwrap().downcast_ref::<MacroCmd>().unwrap();
+    assert_eq!(first.len(), 2);
+    assert_eq!(first.get_name(), None);
+    let group = history.get_undo()[1].as_any().unwrap().downcast_ref::<UndoableGroup>().unwrap();
+    assert_eq!(names(group.get_undoables()), vec!["Rename a to b", "Grow"]);
+}
+
+#[test]
+fn json_round_trip_with_macro_and_group() {
+    let registry = composite_registry();
+    let json = registry.to_json(&composite_history()).unwrap();
+    let mut restored = LinearHistoryImpl::new();
+    let report = registry.from_json(&mut restored, &json).unwrap();
+    assert_eq!(report, LoadReport::default());
+    assert_composites_restored(&restored);
+    assert_eq!(registry.to_json(&restored).unwrap(), json);
+}
+
+#[test]
+fn binary_round_trip_with_macro_and_group() {
+    let registry = composite_registry();
+    let bytes = registry.to_binary(&composite_history()).unwrap();
+    let mut restored = LinearHistoryImpl::new();
+    let report = registry.from_binary(&mut restored, &bytes).unwrap();
+    assert_eq!(report, LoadReport::default());
+    assert_composites_restored(&restored);
+}
+
+#[test]
+fn restored_macro_cannot_be_executed_again() {
+    let registry = composite_registry();
+    let json = registry.to_json(&composite_history()).unwrap();
+    let mut restored = LinearHistoryImpl::new();
+    registry.from_json(&mut restored, &json).unwrap();
+    let macro_cmd = restored.get_undo()[0].as_any().unwrap().downcast_ref::<MacroCmd>().unwrap();
+    assert!(!CommandBase::can_execute(macro_cmd));
+}
+
+#[test]
+fn save_macro_with_unregistered_type_fails() {
+    let error = registry().to_json(&composite_history()).unwrap_err();
+    assert!(error.to_string().contains("Resize 2"));
+}
+
+#[test]
+fn unknown_type_in_group_drops_group() {
+    let json = composite_registry().to_json(&composite_history()).unwrap();
+    let mut without_rename = UndoableTypeRegistry::new();
+    without_rename.register::<Resize>("resize");
+    let mut restored = LinearHistoryImpl::new();
+    let report = without_rename.from_json(&mut restored, &json).unwrap();
+    assert!(restored.get_undo().is_empty());
+    assert_eq!(
+        report,
+        LoadReport {
+            unknown_types: vec!["rename".to_string()],
+            dropped: 2,
+        }
+    );
+}
+
+#[test]
+fn memory_max_round_trip() {
+    let registry = registry();
+    let mut history = history();
+    history.set_memory_max(Some(4096));
+    let bytes = registry.to_binary(&history).unwrap();
+    let mut restored = LinearHistoryImpl::new();
+    registry.from_binary(&mut restored, &bytes).unwrap();
+    assert_eq!(restored.get_memory_max(), Some(4096));
+    let json = registry.to_json(&LinearHistoryImpl::new()).unwrap();
+    registry.from_json(&mut restored, &json).unwrap();
+    assert_eq!(restored.get_memory_max(), None);
+}
+
+#[test]
+fn missing_memory_max_keeps_budget() {
+    let mut restored = LinearHistoryImpl::new();
+    restored.set_memory_max(Some(4096));
+    registry()
+        .from_json(&mut restored, r#"{"size_max":10,"undos":[],"redos":[]}"#)
+        .unwrap();
+    assert_eq!(restored.get_size_max(), 10);
+    assert_eq!(restored.get_memory_max(), Some(4096));
+}
@@ -0,0 +1,39 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use crate::{
+    event::{MouseButton, PointerEvent, UIEvent},
+    interaction::{Interaction, PointData},
+    press::Press,
+};
+
+use super::interaction::{down, record, stopped};
+
+#[test]
+fn press_stops_on_down() {
+    let mut press = Press::new();
+    let (steps, _sub) = record(&press);
+    assert!(press.process_event(&down(3.0, 4.0)));
+    let data = stopped(&steps).unwrap();
+    assert_eq!((data.x, data.y), (3.0, 4.0));
+    assert!(!press.is_running());
+}
+
+#[test]
+fn press_with_any_button() {
+    let mut press = Press::new();
+    let (steps, _sub) = record(&press);
+    press.process_event(&UIEvent::PointerDown(PointerEvent::new(0.0, 0.0, MouseButton::Secondary)));
+    assert_eq!(stopped(&steps).map(|data: PointData| data.button), Some(MouseButton::Secondary));
+}
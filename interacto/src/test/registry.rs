@@ -0,0 +1,185 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::{cell::RefCell, convert::Infallible, rc::Rc};
+
+use crate::{
+    command::{CmdStatus, Command, CommandBase, CustomCmd},
+    registry::{CmdId, CommandsRegistry},
+};
+
+struct StubCmd(u32);
+
+impl CommandBase for StubCmd {}
+
+impl CustomCmd for StubCmd {
+    type Error = Infallible;
+
+    fn execution(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+fn done_cmd(value: u32) -> Command<StubCmd> {
+    let mut cmd = StubCmd(value).as_command();
+    cmd.execute();
+    cmd.done();
+    cmd
+}
+
+fn value_of(registry: &CommandsRegistry, id: CmdId) -> u32 {
+    registry
+        .get(id)
+        .unwrap()
+        .as_any()
+        .downcast_ref::<Command<StubCmd>>()
+        .unwrap()
+        .child
+        .0
+}
+
+#[test]
+fn empty_after_creation() {
+    let registry = CommandsRegistry::new();
+    assert!(registry.is_empty());
+    assert_eq!(registry.get_size_max(), 50);
+}
+
+#[test]
+fn add_command() {
+    let mut registry = CommandsRegistry::new();
+    let id = registry.add(done_cmd(1)).unwrap();
+    assert_eq!(registry.len(), 1);
+    assert_eq!(registry.get(id).unwrap().get_status(), CmdStatus::Done);
+    assert_eq!(value_of(&registry, id), 1);
+}
+
+#[test]
+fn add_commands_order() {
+    let mut registry = CommandsRegistry::new();
+    let id1 = registry.add(done_cmd(1)).unwrap();
+    let id2 = registry.add(done_cmd(2)).unwrap();
+    let ids: Vec<CmdId> = registry.get_commands().map(|(id, _)| id).collect();
+    assert_eq!(ids, vec![id1, id2]);
+}
+
+#[test]
+fn add_when_full_flushes_oldest() {
+    let mut registry = CommandsRegistry::new();
+    registry.set_size_max(2);
+    let removed: Rc<RefCell<Vec<(CmdId, CmdStatus)>>> = Rc::default();
+    let removed_obs = removed.clone();
+    let _sub = registry.subscribe_removed(move |id, cmd| removed_obs.borrow_mut().push((id, cmd.get_status())));
+    let id1 = registry.add(done_cmd(1)).unwrap();
+    let id2 = registry.add(done_cmd(2)).unwrap();
+    let id3 = registry.add(done_cmd(3)).unwrap();
+    assert_eq!(registry.len(), 2);
+    assert!(registry.get(id1).is_none());
+    assert!(registry.get(id2).is_some());
+    assert!(registry.get(id3).is_some());
+    assert_eq!(*removed.borrow(), vec![(id1, CmdStatus::Flushed)]);
+}
+
+#[test]
+fn add_with_zero_size_max() {
+    let mut registry = CommandsRegistry::new();
+    registry.set_size_max(0);
+    let removed: Rc<RefCell<Vec<CmdId>>> = Rc::default();
+    let removed_obs = removed.clone();
+    let _sub = registry.subscribe_removed(move |id, _| removed_obs.borrow_mut().push(id));
+    assert_eq!(registry.add(done_cmd(1)), None);
+    assert!(registry.is_empty());
+    assert!(removed.borrow().is_empty());
+}
+
+#[test]
+fn add_not_done_command_rejected() {
+    let mut registry = CommandsRegistry::new();
+    let mut cmd = StubCmd(1).as_command();
+    cmd.execute();
+    assert_eq!(registry.add(cmd), None);
+    assert_eq!(registry.add(StubCmd(2).as_command()), None);
+    assert!(registry.is_empty());
+}
+
+#[test]
+fn set_size_max_flushes_oldest() {
+    let mut registry = CommandsRegistry::new();
+    registry.add(done_cmd(1)).unwrap();
+    registry.add(done_cmd(2)).unwrap();
+    let id3 = registry.add(done_cmd(3)).unwrap();
+    registry.set_size_max(1);
+    assert_eq!(registry.get_size_max(), 1);
+    let ids: Vec<CmdId> = registry.get_commands().map(|(id, _)| id).collect();
+    assert_eq!(ids, vec![id3]);
+}
+
+#[test]
+fn remove_command() {
+    let mut registry = CommandsRegistry::new();
+    let id = registry.add(done_cmd(1)).unwrap();
+    let removed: Rc<RefCell<Vec<CmdStatus>>> = Rc::default();
+    let removed_obs = removed.clone();
+    let _sub = registry.subscribe_removed(move |_, cmd| removed_obs.borrow_mut().push(cmd.get_status()));
+    assert!(registry.remove(id));
+    assert!(registry.is_empty());
+    assert_eq!(*removed.borrow(), vec![CmdStatus::Flushed]);
+}
+
+#[test]
+fn remove_unknown_command() {
+    let mut registry = CommandsRegistry::new();
+    let id = registry.add(done_cmd(1)).unwrap();
+    registry.remove(id);
+    assert!(!registry.remove(id));
+}
+
+#[test]
+fn cancel_command() {
+    let mut registry = CommandsRegistry::new();
+    let id1 = registry.add(done_cmd(1)).unwrap();
+    let id2 = registry.add(done_cmd(2)).unwrap();
+    let cancelled: Rc<RefCell<Vec<CmdId>>> = Rc::default();
+    let cancelled_obs = cancelled.clone();
+    let _sub = registry.subscribe_removed(move |id, _| cancelled_obs.borrow_mut().push(id));
+    assert!(registry.cancel(id1));
+    assert!(!registry.cancel(id1));
+    assert_eq!(*cancelled.borrow(), vec![id1]);
+    assert_eq!(value_of(&registry, id2), 2);
+}
+
+#[test]
+fn clear() {
+    let mut registry = CommandsRegistry::new();
+    let id1 = registry.add(done_cmd(1)).unwrap();
+    let id2 = registry.add(done_cmd(2)).unwrap();
+    let removed: Rc<RefCell<Vec<CmdId>>> = Rc::default();
+    let removed_obs = removed.clone();
+    let _sub = registry.subscribe_removed(move |id, _| removed_obs.borrow_mut().push(id));
+    registry.clear();
+    assert!(registry.is_empty());
+    assert_eq!(*removed.borrow(), vec![id1, id2]);
+}
+
+#[test]
+fn observe_added_commands() {
+    let mut registry = CommandsRegistry::new();
+    let added: Rc<RefCell<Vec<(CmdId, CmdStatus)>>> = Rc::default();
+    let added_obs = added.clone();
+    let sub = registry.subscribe_added(move |id, cmd| added_obs.borrow_mut().push((id, cmd.get_status())));
+    let id = registry.add(done_cmd(1)).unwrap();
+    drop(sub);
+    registry.add(done_cmd(2)).unwrap();
+    assert_eq!(*added.borrow(), vec![(id, CmdStatus::Done)]);
+}
@@ -0,0 +1,86 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::{cell::RefCell, rc::Rc};
+
+use crate::subject::{Subject, Subscription};
+
+type Log = Rc<RefCell<Vec<String>>>;
+
+fn logger(log: &Log, name: &'static str) -> Box<dyn FnMut(&str)> {
+    let log = log.clone();
+    Box::new(move |value| log.borrow_mut().push(format!("{} {}", name, value)))
+}
+
+#[test]
+fn no_observer_after_creation() {
+    assert_eq!(Subject::<dyn FnMut(&str)>::new().observer_count(), 0);
+}
+
+#[test]
+fn notify_observers_in_order() {
+    let log = Log::default();
+    let subject: Subject<dyn FnMut(&str)> = Subject::new();
+    let _sub1 = subject.subscribe(logger(&log, "a"));
+    let _sub2 = subject.subscribe(logger(&log, "b"));
+    subject.notify(|observer| observer("x"));
+    assert_eq!(*log.borrow(), vec!["a x", "b x"]);
+}
+
+#[test]
+fn drop_subscription_unsubscribes() {
+    let log = Log::default();
+    let subject: Subject<dyn FnMut(&str)> = Subject::new();
+    let sub = subject.subscribe(logger(&log, "a"));
+    drop(sub);
+    subject.notify(|observer| observer("x"));
+    assert!(log.borrow().is_empty());
+    assert_eq!(subject.observer_count(), 0);
+}
+
+#[test]
+fn unsubscribe_keeps_other_observers() {
+    let log = Log::default();
+    let subject: Subject<dyn FnMut(&str)> = Subject::new();
+    let sub = subject.subscribe(logger(&log, "a"));
+    let _sub2 = subject.subscribe(logger(&log, "b"));
+    sub.unsubscribe();
+    subject.notify(|observer| observer("x"));
+    assert_eq!(*log.borrow(), vec!["b x"]);
+}
+
+#[test]
+fn subscription_outlives_subject() {
+    let log = Log::default();
+    let subject: Subject<dyn FnMut(&str)> = Subject::new();
+    let sub = subject.subscribe(logger(&log, "a"));
+    drop(subject);
+    drop(sub);
+}
+
+#[test]
+fn unsubscribe_while_notified() {
+    let log = Log::default();
+    let subject: Subject<dyn FnMut(&str)> = Subject::new();
+    let holder: Rc<RefCell<Option<Subscription>>> = Rc::default();
+    let holder_obs = holder.clone();
+    let log_obs = log.clone();
+    *holder.borrow_mut() = Some(subject.subscribe(Box::new(move |value| {
+        log_obs.borrow_mut().push(value.to_string());
+        holder_obs.borrow_mut().take();
+    })));
+    subject.notify(|observer| observer("x"));
+    subject.notify(|observer| observer("y"));
+    assert_eq!(*log.borrow(), vec!["x"]);
+}
@@ -0,0 +1,339 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{
+    messageformatter::MessageFormatter,
+    treehistory::TreeUndoHistory,
+    undoble::{Undoable, UndoableSnapshot},
+    undohistory::{UndoHistoryBase, UndoableObserver},
+};
+
+type Log = Rc<RefCell<Vec<String>>>;
+
+struct StubUndoable {
+    name: &'static str,
+    log: Log,
+}
+
+impl Undoable for StubUndoable {
+    fn undo(&mut self) {
+        self.log.borrow_mut().push(format!("undo {}", self.name));
+    }
+
+    fn redo(&mut self) {
+        self.log.borrow_mut().push(format!("redo {}", self.name));
+    }
+
+    fn get_undo_name(&self) -> String {
+        self.name.to_string()
+    }
+
+    fn visual_snapshot(&self) -> Option<UndoableSnapshot> {
+        Some(UndoableSnapshot::Text(format!("snapshot {}", self.name)))
+    }
+}
+
+fn undoable(name: &'static str, log: &Log) -> Box<StubUndoable> {
+    Box::new(StubUndoable {
+        name,
+        log: log.clone(),
+    })
+}
+
+/// Builds the tree: a -> b, and a -> c (b undone before adding c).
+fn two_branches(log: &Log) -> TreeUndoHistory {
+    let mut history = TreeUndoHistory::new();
+    history.add(undoable("a", log));
+    history.add(undoable("b", log));
+    history.undo();
+    history.add(undoable("c", log));
+    log.borrow_mut().clear();
+    history
+}
+
+#[test]
+fn empty_after_creation() {
+    let history = TreeUndoHistory::new();
+    assert!(history.get_nodes().is_empty());
+    assert!(history.get_current().is_none());
+    assert!(history.get_last_undo().is_none());
+    assert!(history.get_last_redo().is_none());
+}
+
+#[test]
+fn add_moves_current_node() {
+    let log = Log::default();
+    let mut history = TreeUndoHistory::new();
+    history.add(undoable("a", &log));
+    history.add(undoable("b", &log));
+    assert_eq!(history.get_current(), Some(1));
+    assert_eq!(history.get_last_undo().unwrap().get_undo_name(), "b");
+    assert_eq!(history.get_node(1).unwrap().get_parent(), Some(0));
+    assert_eq!(history.get_root_children(), &[0]);
+}
+
+#[test]
+fn undo_and_redo() {
+    let log = Log::default();
+    let mut history = TreeUndoHistory::new();
+    history.add(undoable("a", &log));
+    history.undo();
+    assert!(history.get_current().is_none());
+    assert_eq!(history.get_last_redo().unwrap().get_undo_name(), "a");
+    history.redo();
+    assert_eq!(history.get_current(), Some(0));
+    assert!(history.get_last_redo().is_none());
+    assert_eq!(*log.borrow(), vec!["undo a", "redo a"]);
+}
+
+#[test]
+fn undo_redo_when_empty_do_nothing() {
+    let mut history = TreeUndoHistory::new();
+    history.undo();
+    history.redo();
+    assert!(history.get_current().is_none());
+}
+
+#[test]
+fn add_after_undo_keeps_branch() {
+    let log = Log::default();
+    let history = two_branches(&log);
+    assert_eq!(history.get_nodes().len(), 3);
+    assert_eq!(history.get_node(0).unwrap().get_children(), &[1, 2]);
+    assert_eq!(history.get_current(), Some(2));
+    assert!(history.get_last_redo().is_none());
+}
+
+#[test]
+fn redo_after_undo_on_new_branch() {
+    let log = Log::default();
+    let mut history = two_branches(&log);
+    history.undo();
+    assert_eq!(history.get_last_redo().unwrap().get_undo_name(), "c");
+    history.redo();
+    assert_eq!(history.get_current(), Some(2));
+}
+
+#[test]
+fn goto_other_branch() {
+    let log = Log::default();
+    let mut history = two_branches(&log);
+    assert!(history.goto(1));
+    assert_eq!(history.get_current(), Some(1));
+    assert_eq!(*log.borrow(), vec!["undo c", "redo b"]);
+}
+
+#[test]
+fn goto_current_does_nothing() {
+    let log = Log::default();
+    let mut history = two_branches(&log);
+    assert!(history.goto(2));
+    assert!(log.borrow().is_empty());
+}
+
+#[test]
+fn goto_ancestor() {
+    let log = Log::default();
+    let mut history = two_branches(&log);
+    assert!(history.goto(0));
+    assert_eq!(history.get_current(), Some(0));
+    assert_eq!(*log.borrow(), vec!["undo c"]);
+    assert_eq!(history.get_last_redo().unwrap().get_undo_name(), "c");
+}
+
+#[test]
+fn goto_descendant_from_root() {
+    let log = Log::default();
+    let mut history = two_branches(&log);
+    history.goto_root();
+    log.borrow_mut().clear();
+    assert!(history.goto(1));
+    assert_eq!(*log.borrow(), vec!["redo a", "redo b"]);
+}
+
+#[test]
+fn goto_root() {
+    let log = Log::default();
+    let mut history = two_branches(&log);
+    history.goto_root();
+    assert!(history.get_current().is_none());
+    assert_eq!(*log.borrow(), vec!["undo c", "undo a"]);
+}
+
+#[test]
+fn goto_unknown_node() {
+    let log = Log::default();
+    let mut history = two_branches(&log);
+    assert!(!history.goto(3));
+    assert_eq!(history.get_current(), Some(2));
+    assert!(log.borrow().is_empty());
+}
+
+#[test]
+fn node_labels() {
+    let log = Log::default();
+    let history = two_branches(&log);
+    let labels: Vec<String> = history
+        .get_nodes()
+        .iter()
+        .map(|node| node.get_undo_name())
+        .collect();
+    assert_eq!(labels, vec!["a", "b", "c"]);
+}
+
+#[test]
+fn clear() {
+    let log = Log::default();
+    let mut history = two_branches(&log);
+    history.clear();
+    assert!(history.get_nodes().is_empty());
+    assert!(history.get_root_children().is_empty());
+    assert!(history.get_current().is_none());
+    assert!(history.get_last_redo().is_none());
+}
+
+type Notifications = Rc<RefCell<Vec<Option<String>>>>;
+
+fn recorder(notifications: &Notifications) -> Box<UndoableObserver> {
+    let notifications = notifications.clone();
+    Box::new(move |undoable| {
+        notifications
+            .borrow_mut()
+            .push(undoable.map(|u| u.get_undo_name()))
+    })
+}
+
+#[test]
+fn add_notifies() {
+    let log = Log::default();
+    let undos = Notifications::default();
+    let redos = Notifications::default();
+    let mut history = TreeUndoHistory::new();
+    let _sub_undos = history.subscribe_undos(recorder(&undos));
+    let _sub_redos = history.subscribe_redos(recorder(&redos));
+    history.add(undoable("a", &log));
+    assert_eq!(*undos.borrow(), vec![Some("a".to_string())]);
+    assert_eq!(*redos.borrow(), vec![None]);
+}
+
+#[test]
+fn undo_redo_notify() {
+    let log = Log::default();
+    let undos = Notifications::default();
+    let redos = Notifications::default();
+    let mut history = two_branches(&log);
+    let _sub_undos = history.subscribe_undos(recorder(&undos));
+    let _sub_redos = history.subscribe_redos(recorder(&redos));
+    history.undo();
+    history.redo();
+    assert_eq!(
+        *undos.borrow(),
+        vec![Some("a".to_string()), Some("c".to_string())]
+    );
+    assert_eq!(*redos.borrow(), vec![Some("c".to_string()), None]);
+}
+
+#[test]
+fn goto_notifies_once() {
+    let log = Log::default();
+    let undos = Notifications::default();
+    let redos = Notifications::default();
+    let mut history = two_branches(&log);
+    let _sub_undos = history.subscribe_undos(recorder(&undos));
+    let _sub_redos = history.subscribe_redos(recorder(&redos));
+    history.goto(1);
+    history.goto(1);
+    history.goto_root();
+    assert_eq!(*undos.borrow(), vec![Some("b".to_string()), None]);
+    assert_eq!(*redos.borrow(), vec![None, Some("a".to_string())]);
+}
+
+#[test]
+fn clear_notifies() {
+    let log = Log::default();
+    let undos = Notifications::default();
+    let mut history = two_branches(&log);
+    let _sub = history.subscribe_undos(recorder(&undos));
+    history.clear();
+    history.clear();
+    assert_eq!(*undos.borrow(), vec![None]);
+}
+
+struct PrefixFormatter;
+
+impl MessageFormatter for PrefixFormatter {
+    fn format_undo(&self, name: &str) -> String {
+        format!("Undo {}", name)
+    }
+
+    fn format_redo(&self, name: &str) -> String {
+        format!("Redo {}", name)
+    }
+}
+
+#[test]
+fn messages() {
+    let log = Log::default();
+    let mut history = two_branches(&log);
+    history.set_message_formatter(Box::new(PrefixFormatter));
+    history.undo();
+    assert_eq!(history.get_last_undo_message().as_deref(), Some("Undo a"));
+    assert_eq!(history.get_last_redo_message().as_deref(), Some("Redo c"));
+    history.goto_root();
+    assert_eq!(history.get_last_or_empty_undo_message(), "");
+}
+
+#[test]
+fn node_snapshots() {
+    let log = Log::default();
+    let history = two_branches(&log);
+    let snapshots: Vec<Option<UndoableSnapshot>> = history
+        .get_nodes()
+        .iter()
+        .map(|node| node.get_visual_snapshot())
+        .collect();
+    assert_eq!(
+        snapshots,
+        vec![
+            Some(UndoableSnapshot::Text("snapshot a".to_string())),
+            Some(UndoableSnapshot::Text("snapshot b".to_string())),
+            Some(UndoableSnapshot::Text("snapshot c".to_string())),
+        ]
+    );
+}
+
+#[test]
+fn last_snapshots() {
+    let log = Log::default();
+    let mut history = two_branches(&log);
+    history.undo();
+    assert_eq!(
+        history.get_last_undo_snapshot(),
+        Some(UndoableSnapshot::Text("snapshot a".to_string()))
+    );
+    assert_eq!(
+        history.get_last_redo_snapshot(),
+        Some(UndoableSnapshot::Text("snapshot c".to_string()))
+    );
+}
+
+#[test]
+fn memory_footprint_counts_all_branches() {
+    let log = Log::default();
+    let history = two_branches(&log);
+    let node = std::mem::size_of::<StubUndoable>();
+    assert_eq!(history.get_memory_footprint(), 3 * node);
+}
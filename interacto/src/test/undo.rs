@@ -12,18 +12,26 @@
  * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-use crate::{command::CustomCmd, undo::Undo, undoble::Undoable, undohistory::UndoHistoryBase};
+use crate::{
+    command::CommandBase,
+    historyhandle::HistoryHandle,
+    linearhistory::LinearHistoryImpl,
+    mergepolicy::MergePolicy,
+    undo::{Redo, Undo},
+    undoble::Undoable,
+    undohistory::UndoHistoryBase,
+};
 use mockall::mock;
 
 mock! {
     FakeHistory {}
     impl UndoHistoryBase for FakeHistory {
-        fn undo(&self);
-        fn redo(&self);
-        fn clear(&self);
-        fn add(&self, undoable: Box<dyn Undoable>);
+        fn undo(&mut self);
+        fn redo(&mut self);
+        fn clear(&mut self);
+        fn add(&mut self, undoable: Box<dyn Undoable>);
         fn get_last_undo(&self) -> Option<&'static dyn Undoable>;
-        fn get_last_redo(&self) -> Option<Box<dyn Undoable + 'static>>;
+        fn get_last_redo(&self) -> Option<&'static dyn Undoable>;
     }
 }
 mock! {
@@ -35,20 +43,129 @@ mock! {
     }
 }
 
+fn named(name: &'static str) -> MockFakeUndoable {
+    let mut undoable = MockFakeUndoable::new();
+    undoable.expect_get_undo_name().return_const(name.to_string());
+    undoable
+}
+
+fn shared(history: MockFakeHistory) -> HistoryHandle {
+    HistoryHandle::new(history).into()
+}
+
+fn history_with(undoable: MockFakeUndoable) -> HistoryHandle {
+    let history = HistoryHandle::new(LinearHistoryImpl::new());
+    history.add(Box::new(undoable));
+    history.into()
+}
+
 #[test]
+#[allow(clippy::bool_assert_comparison)]
 fn cannot_do() {
     let mut collector = MockFakeHistory::new();
     collector.expect_get_last_undo().returning(|| None);
     assert_eq!(
-        Undo::new(Box::new(collector)).as_command().can_execute(),
+        Undo::new(shared(collector)).as_command().can_execute(),
         false
     );
 }
 
-// #[test]
-// fn with_undoable() {
-//     let mut collector = MockFakeHistory::new();
-//     let undoable: MockFakeUndoable = MockFakeUndoable::new();
-//     collector.expect_get_last_undo().returning(|| Some(&undoable));
-//     assert_eq!(Undo::new(Box::new(collector)).as_command().can_execute(), true);
-// }
+#[test]
+fn with_undoable() {
+    let history = history_with(MockFakeUndoable::new());
+    assert!(Undo::new(history).as_command().can_execute());
+}
+
+#[test]
+fn undo_calls_history() {
+    let mut undoable = MockFakeUndoable::new();
+    undoable.expect_undo().times(1).return_const(());
+    let history = history_with(undoable);
+    assert!(Undo::new(history.clone()).as_command().execute().is_executed());
+    assert!(history.can_redo());
+}
+
+#[test]
+fn redo_cannot_do() {
+    let mut collector = MockFakeHistory::new();
+    collector.expect_get_last_redo().returning(|| None);
+    assert!(!Redo::new(shared(collector)).as_command().can_execute());
+}
+
+#[test]
+fn redo_calls_history() {
+    let mut undoable = MockFakeUndoable::new();
+    undoable.expect_undo().times(1).return_const(());
+    undoable.expect_redo().times(1).return_const(());
+    let history = history_with(undoable);
+    history.undo();
+    assert!(Redo::new(history.clone()).as_command().execute().is_executed());
+    assert!(history.can_undo());
+}
+
+/// A history that only manages its stacks, and relies on the default features.
+#[derive(Default)]
+struct StacksOnly {
+    undos: Vec<Box<dyn Undoable>>,
+    redos: Vec<Box<dyn Undoable>>,
+}
+
+impl UndoHistoryBase for StacksOnly {
+    fn undo(&mut self) {
+        if let Some(mut undoable) = self.undos.pop() {
+            undoable.undo();
+            self.redos.push(undoable);
+        }
+    }
+
+    fn redo(&mut self) {
+        if let Some(mut undoable) = self.redos.pop() {
+            undoable.redo();
+            self.undos.push(undoable);
+        }
+    }
+
+    fn clear(&mut self) {
+        self.undos.clear();
+        self.redos.clear();
+    }
+
+    fn add(&mut self, undoable: Box<dyn Undoable>) {
+        self.undos.push(undoable);
+        self.redos.clear();
+    }
+
+    fn get_last_undo(&self) -> Option<&dyn Undoable> {
+        self.undos.last().map(|undoable| undoable.as_ref())
+    }
+
+    fn get_last_redo(&self) -> Option<&dyn Undoable> {
+        self.redos.last().map(|undoable| undoable.as_ref())
+    }
+}
+
+#[test]
+fn history_with_default_features() {
+    let mut history = StacksOnly::default();
+    history.set_merge_policy(MergePolicy::Always);
+    assert_eq!(history.get_merge_policy(), MergePolicy::Never);
+    history.begin_group("group");
+    assert!(!history.is_grouping());
+    history.add(Box::new(named("a")));
+    history.add(Box::new(named("b")));
+    history.end_group();
+    assert_eq!(history.get_last_undo_message(), Some("b".to_string()));
+    assert_eq!(history.undos.len(), 2);
+}
+
+#[test]
+fn undo_with_history_with_default_features() {
+    let mut undoable = named("a");
+    undoable.expect_undo().times(1).return_const(());
+    let history: HistoryHandle = HistoryHandle::new(StacksOnly::default()).into();
+    history.add(Box::new(undoable));
+    let mut cmd = Undo::new(history.clone()).as_command();
+    assert!(cmd.execute().is_executed());
+    assert!(!history.can_undo());
+    assert!(history.can_redo());
+}
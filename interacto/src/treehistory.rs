@@ -0,0 +1,410 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use crate::{
+    clock::Clock,
+    historygroup::GroupStack,
+    mergepolicy::{Coalescer, MergePolicy},
+    messageformatter::{MessageFormatter, NameFormatter},
+    subject::{Subject, Subscription},
+    undoble::{Undoable, UndoableSnapshot},
+    undohistory::{UndoHistoryBase, UndoableObserver},
+};
+
+/**
+ * A node of a tree undo history.
+ */
+pub struct UndoableTreeNode {
+    /**
+     * The identifier of the node, unique in its history.
+     */
+    id: usize,
+
+    /**
+     * The undoable object of the node.
+     */
+    undoable: Box<dyn Undoable>,
+
+    /**
+     * The parent node, or none if the parent is the root of the tree.
+     */
+    parent: Option<usize>,
+
+    /**
+     * The children nodes, in their order of creation.
+     */
+    children: Vec<usize>,
+
+    /**
+     * The last child node that has been undone, that is the node to redo.
+     */
+    last_child_undone: Option<usize>,
+}
+
+impl UndoableTreeNode {
+    pub fn get_id(&self) -> usize {
+        self.id
+    }
+
+    pub fn get_undoable(&self) -> &dyn Undoable {
+        self.undoable.as_ref()
+    }
+
+    /**
+     * @returns The name of the undoable object of the node.
+     */
+    pub fn get_undo_name(&self) -> String {
+        self.undoable.get_undo_name()
+    }
+
+    /**
+     * @returns The visual snapshot of the undoable object of the node, if any.
+     */
+    pub fn get_visual_snapshot(&self) -> Option<UndoableSnapshot> {
+        self.undoable.visual_snapshot()
+    }
+
+    /**
+     * @returns The identifier of the parent node, or none if the parent is the root of the tree.
+     */
+    pub fn get_parent(&self) -> Option<usize> {
+        self.parent
+    }
+
+    pub fn get_children(&self) -> &[usize] {
+        &self.children
+    }
+}
+
+/**
+ * An undo history that keeps all the branches of the undoable objects:
+ * adding an undoable object after an undo creates a new branch instead of
+ * removing the undone objects.
+ * Since it never removes nodes, it does not implement 'UndoHistory': it has neither a max size
+ * nor a memory budget ('UndoHistory::set_memory_max'), and cannot be saved (see 'persistence').
+ * 'get_memory_footprint' reports how much memory its nodes hold.
+ */
+pub struct TreeUndoHistory {
+    /**
+     * The nodes of the tree, indexed by their identifier.
+     */
+    nodes: Vec<UndoableTreeNode>,
+
+    /**
+     * The children nodes of the root.
+     */
+    root_children: Vec<usize>,
+
+    /**
+     * The last child node of the root that has been undone.
+     */
+    root_last_child_undone: Option<usize>,
+
+    /**
+     * The current node, or none if the current node is the root.
+     */
+    current: Option<usize>,
+
+    undo_publisher: Subject<UndoableObserver>,
+
+    redo_publisher: Subject<UndoableObserver>,
+
+    /**
+     * Formats the undo and redo messages.
+     */
+    formatter: Box<dyn MessageFormatter>,
+
+    /**
+     * Merges the added undoable objects according to the merge policy.
+     */
+    coalescer: Coalescer,
+
+    /**
+     * The groups being built.
+     */
+    groups: GroupStack,
+}
+
+impl TreeUndoHistory {
+    pub fn new() -> Self {
+        Self {
+            nodes: Vec::new(),
+            root_children: Vec::new(),
+            root_last_child_undone: None,
+            current: None,
+            undo_publisher: Subject::new(),
+            redo_publisher: Subject::new(),
+            formatter: Box::new(NameFormatter),
+            coalescer: Coalescer::new(),
+            groups: GroupStack::new(),
+        }
+    }
+
+    /**
+     * @returns The identifier of the current node, or none if the current node is the root.
+     */
+    pub fn get_current(&self) -> Option<usize> {
+        self.current
+    }
+
+    /**
+     * @returns The number of bytes the undoable objects of all the nodes hold
+     * (see 'Undoable::memory_footprint').
+     */
+    pub fn get_memory_footprint(&self) -> usize {
+        self.nodes.iter().map(|node| node.undoable.memory_footprint()).sum()
+    }
+
+    pub fn get_node(&self, id: usize) -> Option<&UndoableTreeNode> {
+        self.nodes.get(id)
+    }
+
+    /**
+     * @returns All the nodes of the tree, in their order of creation.
+     */
+    pub fn get_nodes(&self) -> &[UndoableTreeNode] {
+        &self.nodes
+    }
+
+    /**
+     * @returns The identifiers of the children nodes of the root.
+     */
+    pub fn get_root_children(&self) -> &[usize] {
+        &self.root_children
+    }
+
+    /**
+     * Moves to the given node by undoing and redoing the undoable objects along
+     * the path between the current node and the given one.
+     * @param id - The identifier of the targeted node.
+     * @returns False if no node has this identifier.
+     */
+    pub fn goto(&mut self, id: usize) -> bool {
+        if id >= self.nodes.len() {
+            return false;
+        }
+        if self.current != Some(id) {
+            self.goto_node(Some(id));
+            self.publish();
+        }
+        true
+    }
+
+    /**
+     * Moves to the root of the tree by undoing all the undoable objects
+     * of the current branch.
+     */
+    pub fn goto_root(&mut self) {
+        if self.current.is_some() {
+            self.goto_node(None);
+            self.publish();
+        }
+    }
+
+    fn goto_node(&mut self, target: Option<usize>) {
+        self.coalescer.reset();
+        let target_path = self.path_from_root(target);
+        let current_path = self.path_from_root(self.current);
+        let common = current_path
+            .iter()
+            .zip(target_path.iter())
+            .take_while(|(a, b)| a == b)
+            .count();
+
+        for _ in common..current_path.len() {
+            self.undo_current();
+        }
+        for &id in &target_path[common..] {
+            self.redo_node(id);
+        }
+    }
+
+    /**
+     * @returns The identifiers of the nodes from the first child of the root to the given node.
+     */
+    fn path_from_root(&self, node: Option<usize>) -> Vec<usize> {
+        let mut path = Vec::new();
+        let mut current = node;
+        while let Some(id) = current {
+            path.push(id);
+            current = self.nodes[id].parent;
+        }
+        path.reverse();
+        path
+    }
+
+    fn last_child_undone(&self, node: Option<usize>) -> Option<usize> {
+        match node {
+            Some(id) => self.nodes[id].last_child_undone,
+            None => self.root_last_child_undone,
+        }
+    }
+
+    fn set_last_child_undone(&mut self, node: Option<usize>, child: Option<usize>) {
+        match node {
+            Some(id) => self.nodes[id].last_child_undone = child,
+            None => self.root_last_child_undone = child,
+        }
+    }
+
+    fn redo_node(&mut self, id: usize) {
+        self.nodes[id].undoable.redo();
+        self.current = Some(id);
+    }
+
+    /**
+     * Undoes the current node without notifying the observers.
+     * @returns False if the current node is the root.
+     */
+    fn undo_current(&mut self) -> bool {
+        match self.current {
+            Some(id) => {
+                self.nodes[id].undoable.undo();
+                let parent = self.nodes[id].parent;
+                self.set_last_child_undone(parent, Some(id));
+                self.current = parent;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn publish(&self) {
+        let last_undo = self.get_last_undo();
+        self.undo_publisher.notify(|observer| observer(last_undo));
+        let last_redo = self.get_last_redo();
+        self.redo_publisher.notify(|observer| observer(last_redo));
+    }
+}
+
+impl Default for TreeUndoHistory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UndoHistoryBase for TreeUndoHistory {
+    fn undo(&mut self) {
+        self.coalescer.reset();
+        if self.undo_current() {
+            self.publish();
+        }
+    }
+
+    fn redo(&mut self) {
+        if let Some(id) = self.last_child_undone(self.current) {
+            self.coalescer.reset();
+            self.redo_node(id);
+            self.publish();
+        }
+    }
+
+    fn clear(&mut self) {
+        let had_nodes = !self.nodes.is_empty();
+        self.groups.clear();
+        self.coalescer.reset();
+        self.nodes.clear();
+        self.root_children.clear();
+        self.root_last_child_undone = None;
+        self.current = None;
+        if had_nodes {
+            self.publish();
+        }
+    }
+
+    fn add(&mut self, undoable: Box<dyn Undoable>) {
+        let Some(undoable) = self.groups.push(undoable) else {
+            return;
+        };
+        // Only a leaf can receive merges: the branches of a node depend on its effect.
+        let last = self
+            .current
+            .map(|id| &mut self.nodes[id])
+            .filter(|node| node.children.is_empty())
+            .map(|node| &mut node.undoable);
+        if self.coalescer.merge(last, undoable.as_ref()) {
+            self.publish();
+            return;
+        }
+
+        let id = self.nodes.len();
+        self.nodes.push(UndoableTreeNode {
+            id,
+            undoable,
+            parent: self.current,
+            children: Vec::new(),
+            last_child_undone: None,
+        });
+        match self.current {
+            Some(parent) => self.nodes[parent].children.push(id),
+            None => self.root_children.push(id),
+        }
+        self.current = Some(id);
+        self.publish();
+    }
+
+    fn begin_group(&mut self, name: &str) {
+        self.groups.begin(name);
+    }
+
+    fn end_group(&mut self) {
+        if let Some(group) = self.groups.end() {
+            self.add(group);
+        }
+    }
+
+    fn abandon_group(&mut self) {
+        self.groups.abandon();
+    }
+
+    fn is_grouping(&self) -> bool {
+        self.groups.is_open()
+    }
+
+    fn get_merge_policy(&self) -> MergePolicy {
+        self.coalescer.get_policy()
+    }
+
+    fn set_merge_policy(&mut self, policy: MergePolicy) {
+        self.coalescer.set_policy(policy);
+    }
+
+    fn set_clock(&mut self, clock: Box<dyn Clock>) {
+        self.coalescer.set_clock(clock);
+    }
+
+    fn get_last_undo(&self) -> Option<&dyn Undoable> {
+        self.current.map(|id| self.nodes[id].get_undoable())
+    }
+
+    fn get_last_redo(&self) -> Option<&dyn Undoable> {
+        self.last_child_undone(self.current).map(|id| self.nodes[id].get_undoable())
+    }
+
+    fn subscribe_undos(&self, observer: Box<UndoableObserver>) -> Subscription {
+        self.undo_publisher.subscribe(observer)
+    }
+
+    fn subscribe_redos(&self, observer: Box<UndoableObserver>) -> Subscription {
+        self.redo_publisher.subscribe(observer)
+    }
+
+    fn get_message_formatter(&self) -> &dyn MessageFormatter {
+        self.formatter.as_ref()
+    }
+
+    fn set_message_formatter(&mut self, formatter: Box<dyn MessageFormatter>) {
+        self.formatter = formatter;
+    }
+}
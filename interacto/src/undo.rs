@@ -12,49 +12,61 @@
  * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-use crate::{undohistory::UndoHistoryBase, command::CustomCmd};
+use std::convert::Infallible;
+
+use crate::{historyhandle::HistoryHandle, command::{CommandBase, CustomCmd}};
 
 pub struct Undo {
-    history: Box<dyn UndoHistoryBase>
+    history: HistoryHandle
 }
 
 impl Undo {
-    pub fn new(history: Box<dyn UndoHistoryBase>) -> Self {
+    pub fn new(history: impl Into<HistoryHandle>) -> Self {
         Self {
-            history
+            history: history.into()
         }
     }
 }
 
-impl CustomCmd for Undo {
-    fn execution(&mut self) {
-        self.history.undo();
+impl CommandBase for Undo {
+    fn can_execute(&self) -> bool {
+        self.history.can_undo()
     }
+}
 
-    fn can_execute(&self) -> bool {
-        self.history.get_last_undo().is_some()
+impl CustomCmd for Undo {
+    type Error = Infallible;
+
+    fn execution(&mut self) -> Result<(), Self::Error> {
+        self.history.undo();
+        Ok(())
     }
 }
 
 pub struct Redo {
-    history: Box<dyn UndoHistoryBase>
+    history: HistoryHandle
 }
 
 impl Redo {
-    pub fn new(history: Box<dyn UndoHistoryBase>) -> Self {
+    pub fn new(history: impl Into<HistoryHandle>) -> Self {
         Self {
-            history
+            history: history.into()
         }
     }
 }
 
-impl CustomCmd for Redo {
-    fn execution(&mut self) {
-        self.history.redo();
+impl CommandBase for Redo {
+    fn can_execute(&self) -> bool {
+        self.history.can_redo()
     }
+}
 
-    fn can_execute(&self) -> bool {
-        self.history.get_last_redo().is_some()
+impl CustomCmd for Redo {
+    type Error = Infallible;
+
+    fn execution(&mut self) -> Result<(), Self::Error> {
+        self.history.redo();
+        Ok(())
     }
 }
 
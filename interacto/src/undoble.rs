@@ -12,6 +12,25 @@
  * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
  */
 
+use std::any::Any;
+
+/**
+ * A preview of the impact of an undoable object, for example to show it in a history panel.
+ */
+#[derive(PartialEq, Clone, Debug)]
+pub enum UndoableSnapshot {
+    /** A textual description. */
+    Text(String),
+    /** An SVG document. */
+    Svg(String),
+    /** A thumbnail made of RGBA pixels, row by row: 'pixels' contains 'width * height * 4' bytes. */
+    Rgba {
+        width: u32,
+        height: u32,
+        pixels: Vec<u8>,
+    },
+}
+
 /**
  * An interface for undoable objects.
  */
@@ -31,9 +50,39 @@ pub trait Undoable {
      */
     fn get_undo_name(&self) -> String;
 
-    // /**
-    //  * Gives some information about the impact of a command.
-    //  * @returns Information about the impact of the commmand as an SVG element or text.
-    //  */
-    // getVisualSnapshot() -> UndoableSnapshot;
+    /**
+     * Gives some information about the impact of a command.
+     * @returns Information about the impact of the command as text, SVG or a thumbnail,
+     * or none if the command provides no snapshot.
+     */
+    fn visual_snapshot(&self) -> Option<UndoableSnapshot> {
+        None
+    }
+
+    /**
+     * @returns The number of bytes the undoable object holds, used by histories that limit their memory usage.
+     * By default, the size of the object itself: undoable objects that own heap data (images, buffers, etc.)
+     * should override it to include that data.
+     */
+    fn memory_footprint(&self) -> usize {
+        std::mem::size_of_val(self)
+    }
+
+    /**
+     * Tries to merge the next undoable object into this one, so that both are undone as a single step.
+     * Histories call it according to their merge policy, for example while typing text or dragging a slider.
+     * @param next - The undoable object added after this one. Use 'as_any' to downcast it.
+     * @returns True if the next object has been merged into this one: the history then drops it.
+     */
+    fn merge_with(&mut self, _next: &dyn Undoable) -> bool {
+        false
+    }
+
+    /**
+     * @returns The undoable object as 'Any', to downcast it (see 'merge_with').
+     * Undoable objects that support merging should return 'Some(self)'.
+     */
+    fn as_any(&self) -> Option<&dyn Any> {
+        None
+    }
 }
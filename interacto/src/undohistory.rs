@@ -12,32 +12,96 @@
  * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-use crate::undoble::Undoable;
+use crate::{
+    clock::Clock,
+    mergepolicy::MergePolicy,
+    messageformatter::{MessageFormatter, NameFormatter},
+    subject::Subscription,
+    undoble::{Undoable, UndoableSnapshot},
+};
+
+/**
+ * An observer of the last undoable (or redoable) object of a history.
+ * It receives none when the history has no last object anymore.
+ */
+pub type UndoableObserver = dyn FnMut(Option<&dyn Undoable>);
 
 /**
  * The base trait for undo histories.
+ * A history owns its undoable objects: the operations that change the stacks take `&mut self`
+ * and the getters only lend the stored objects.
+ * To share a history, wrap it into a `HistoryHandle`.
+ * Only the management of the undo and redo stacks is required: the other features (groups,
+ * merging, messages, notifications) have default implementations for histories that do not
+ * support them.
  */
 pub trait UndoHistoryBase {
     /**
      * Undoes the last undoable object.
      */
-    fn undo(&self);
+    fn undo(&mut self);
 
     /**
      * Redoes the last undoable object.
      */
-    fn redo(&self);
+    fn redo(&mut self);
 
     /**
-     * Removes all the undoable objects of the collector.
+     * Removes all the undoable objects of the collector, including the ones of the open groups.
      */
-    fn clear(&self);
+    fn clear(&mut self);
 
     /**
-     * Adds an undoable object to the collector.
+     * Adds an undoable object to the collector, or to the innermost open group.
+     * According to the merge policy, the object may be merged into the last undoable object.
      * @param undoable - The undoable object to add.
      */
-    fn add(&self, undoable: Box<dyn Undoable>);
+    fn add(&mut self, undoable: Box<dyn Undoable>);
+
+    /**
+     * Starts a group: the undoable objects added until the end of the group form a single undo step.
+     * Groups can be nested: a nested group forms a single step of its enclosing group.
+     * By default, groups are not supported: the undoable objects are added one by one.
+     * @param name - The undo name of the group.
+     */
+    fn begin_group(&mut self, _name: &str) {}
+
+    /**
+     * Ends the innermost group. The group is added to the history, unless it is empty.
+     */
+    fn end_group(&mut self) {}
+
+    /**
+     * Ends the innermost group by undoing its undoable objects, which are not added to the history.
+     */
+    fn abandon_group(&mut self) {}
+
+    /**
+     * @returns True if a group is open.
+     */
+    fn is_grouping(&self) -> bool {
+        false
+    }
+
+    /**
+     * @returns The policy that defines when added undoable objects are merged into the last undoable object.
+     * By default, 'MergePolicy::Never'.
+     */
+    fn get_merge_policy(&self) -> MergePolicy {
+        MergePolicy::Never
+    }
+
+    /**
+     * @param policy - The policy that defines when added undoable objects are merged into the last undoable object.
+     * Ignored by default, as histories do not merge undoable objects by default.
+     */
+    fn set_merge_policy(&mut self, _policy: MergePolicy) {}
+
+    /**
+     * Replaces the clock that times the added undoable objects for the 'MergePolicy::Within'
+     * policy (the system clock by default). Ignored by default.
+     */
+    fn set_clock(&mut self, _clock: Box<dyn Clock>) {}
 
     /**
      * @returns The last undoable object or undefined if there is no last object.
@@ -47,53 +111,121 @@ pub trait UndoHistoryBase {
     /**
      * @returns The last redoable object or undefined if there is no last object.
      */
-    fn get_last_redo(&self) -> Option<Box<dyn Undoable>>;
-
-    // /**
-    //  * @returns The last undoable object name or undefined if there is no last object.
-    //  */
-    // fn getLastUndoMessage(): string | undefined;
-
-    // /**
-    //  * @returns The last redoable object name or undefined if there is no last object.
-    //  */
-    // fn getLastRedoMessage(): string | undefined;
-
-    // /**
-    //  * @returns The last undoable object name or an empty string if there is no last object.
-    //  */
-    // fn getLastOrEmptyUndoMessage(): string;
-
-    // /**
-    //  * @returns The last redoable object name or an empty string if there is no last object.
-    //  */
-    // fn getLastOrEmptyRedoMessage(): string;
-
-    // /**
-    //  * A stream for observing changes regarding the last undoable object.
-    //  * @returns An observable value of optional undoable objects: if empty, this means
-    //  * that no undoable object are stored anymore.
-    //  */
-    // undosObservable(): Observable<Undoable | undefined>;
-
-    // /**
-    //  * A stream for observing changes regarding the last redoable object.
-    //  * @returns An observable value of optional redoable objects: if empty, this means
-    //  * that no redoable object are stored anymore.
-    //  */
-    // redosObservable(): Observable<Undoable | undefined>;
+    fn get_last_redo(&self) -> Option<&dyn Undoable>;
+
+    /**
+     * @returns The formatter used to produce the undo and redo messages.
+     * By default, 'NameFormatter'.
+     */
+    fn get_message_formatter(&self) -> &dyn MessageFormatter {
+        &NameFormatter
+    }
+
+    /**
+     * @param formatter - The formatter used to produce the undo and redo messages.
+     * Ignored by default.
+     */
+    fn set_message_formatter(&mut self, _formatter: Box<dyn MessageFormatter>) {}
+
+    /**
+     * @returns The formatted name of the last undoable object or none if there is no last object.
+     */
+    fn get_last_undo_message(&self) -> Option<String> {
+        self.get_last_undo()
+            .map(|undoable| self.get_message_formatter().format_undo(&undoable.get_undo_name()))
+    }
+
+    /**
+     * @returns The formatted name of the last redoable object or none if there is no last object.
+     */
+    fn get_last_redo_message(&self) -> Option<String> {
+        self.get_last_redo()
+            .map(|undoable| self.get_message_formatter().format_redo(&undoable.get_undo_name()))
+    }
+
+    /**
+     * @returns The formatted name of the last undoable object or an empty string if there is no last object.
+     */
+    fn get_last_or_empty_undo_message(&self) -> String {
+        self.get_last_undo_message().unwrap_or_default()
+    }
+
+    /**
+     * @returns The formatted name of the last redoable object or an empty string if there is no last object.
+     */
+    fn get_last_or_empty_redo_message(&self) -> String {
+        self.get_last_redo_message().unwrap_or_default()
+    }
+
+    /**
+     * @returns The visual snapshot of the last undoable object, if any.
+     */
+    fn get_last_undo_snapshot(&self) -> Option<UndoableSnapshot> {
+        self.get_last_undo().and_then(|undoable| undoable.visual_snapshot())
+    }
+
+    /**
+     * @returns The visual snapshot of the last redoable object, if any.
+     */
+    fn get_last_redo_snapshot(&self) -> Option<UndoableSnapshot> {
+        self.get_last_redo().and_then(|undoable| undoable.visual_snapshot())
+    }
+
+    /**
+     * Observes the changes regarding the last undoable object.
+     * The observer receives none when no undoable object is stored anymore.
+     * Observers are notified while the history is being modified, so they must not access the history.
+     * By default, changes are not notified: the observer is dropped.
+     * @returns The subscription of the observer: the observer is removed when it is dropped.
+     */
+    fn subscribe_undos(&self, _observer: Box<UndoableObserver>) -> Subscription {
+        Subscription::empty()
+    }
+
+    /**
+     * Observes the changes regarding the last redoable object.
+     * The observer receives none when no redoable object is stored anymore.
+     * Observers are notified while the history is being modified, so they must not access the history.
+     * By default, changes are not notified: the observer is dropped.
+     * @returns The subscription of the observer: the observer is removed when it is dropped.
+     */
+    fn subscribe_redos(&self, _observer: Box<UndoableObserver>) -> Subscription {
+        Subscription::empty()
+    }
 }
 
 pub trait UndoHistory: UndoHistoryBase {
     /**
      * @returns The stack of saved undoable objects.
      */
-    fn get_undo(&self) -> Vec<Box<dyn Undoable>>;
+    fn get_undo(&self) -> &[Box<dyn Undoable>];
 
     /**
      * @returns The stack of saved redoable objects
      */
-    fn get_redo(&self) -> Vec<Box<dyn Undoable>>;
+    fn get_redo(&self) -> &[Box<dyn Undoable>];
+
+    /**
+     * @returns The visual snapshots of the saved undoable objects, in the order of the undo stack.
+     */
+    fn get_undo_snapshots(&self) -> Vec<Option<UndoableSnapshot>> {
+        self.get_undo().iter().map(|undoable| undoable.visual_snapshot()).collect()
+    }
+
+    /**
+     * @returns The visual snapshots of the saved redoable objects, in the order of the redo stack.
+     */
+    fn get_redo_snapshots(&self) -> Vec<Option<UndoableSnapshot>> {
+        self.get_redo().iter().map(|undoable| undoable.visual_snapshot()).collect()
+    }
+
+    /**
+     * Replaces the content of the history, for example to restore a saved history.
+     * The undoable objects are neither undone nor redone.
+     * @param undos - The undoable objects, in the order of the undo stack (the last one is the next to undo).
+     * @param redos - The redoable objects, in the order of the redo stack (the last one is the next to redo).
+     */
+    fn restore(&mut self, undos: Vec<Box<dyn Undoable>>, redos: Vec<Box<dyn Undoable>>);
 
     /**
      * @returns The max number of saved undoable objects.
@@ -104,4 +236,26 @@ pub trait UndoHistory: UndoHistoryBase {
      * @param max - The max number of saved undoable objects. Must be great than 0.
      */
     fn set_size_max(&mut self, max: u64);
+
+    /**
+     * @returns The max number of bytes the saved undoable objects can hold (see 'Undoable::memory_footprint'),
+     * or none if the memory usage is not limited.
+     */
+    fn get_memory_max(&self) -> Option<usize>;
+
+    /**
+     * Limits the memory usage of the history, in addition to the max number of undoable objects:
+     * the oldest undoable objects are removed until the saved ones fit the budget.
+     * The last undoable object is always kept, even if it exceeds the budget on its own.
+     * 'TreeUndoHistory' keeps all its nodes and has no such budget.
+     * @param max - The max number of bytes, or none to not limit the memory usage.
+     */
+    fn set_memory_max(&mut self, max: Option<usize>);
+
+    /**
+     * @returns The number of bytes the saved undoable objects hold.
+     */
+    fn get_memory_footprint(&self) -> usize {
+        self.get_undo().iter().map(|undoable| undoable.memory_footprint()).sum()
+    }
 }